@@ -52,9 +52,41 @@
 //! ```
 //!
 use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::hash::Hash;
 use std::iter::Sum;
+use std::ops::Range;
 
-use num_traits::{NumAssignOps, NumOps, One, Zero};
+use num_traits::{Float, NumAssignOps, NumCast, NumOps, One, Zero};
+
+/// Operation counters, to check the complexity of the algorithms.
+///
+/// Counters are per thread and only available with the `count-ops` feature.
+#[cfg(feature = "count-ops")]
+pub mod count_ops {
+    use std::cell::Cell;
+
+    thread_local! {
+        static RATIOS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// Reset the counters of the current thread
+    pub fn reset() {
+        RATIOS.with(|c| c.set(0));
+    }
+
+    /// Number of item aspect ratio computations, each followed by a comparison,
+    /// on the current thread since the last [`reset`]
+    pub fn ratios() -> usize {
+        RATIOS.with(|c| c.get())
+    }
+
+    pub(crate) fn count_ratio() {
+        RATIOS.with(|c| c.set(c.get() + 1));
+    }
+}
 
 /// A simple rect
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -65,6 +97,14 @@ pub struct Rect<N> {
     pub h: N,
 }
 
+impl<N> Rect<N> {
+    /// Create a Rect by applying `f` to each of `x`, `y`, `w` and `h`
+    #[inline]
+    pub fn map<M, F: Fn(N) -> M>(self, f: F) -> Rect<M> {
+        Rect { x: f(self.x), y: f(self.y), w: f(self.w), h: f(self.h) }
+    }
+}
+
 impl<N> Rect<N>
 where
     N: Zero,
@@ -79,16 +119,256 @@ impl<N> Rect<N>
 where
     N: NumOps + Copy,
 {
-    /// Flip this rect horizontally within a container of height `h`
+    /// Left edge of this rect
+    #[inline]
+    pub fn min_x(&self) -> N {
+        self.x
+    }
+
+    /// Top edge of this rect
+    #[inline]
+    pub fn min_y(&self) -> N {
+        self.y
+    }
+
+    /// Right edge of this rect
+    #[inline]
+    pub fn max_x(&self) -> N {
+        self.x + self.w
+    }
+
+    /// Bottom edge of this rect
+    #[inline]
+    pub fn max_y(&self) -> N {
+        self.y + self.h
+    }
+
+    /// Flip this rect horizontally within a container of width `container_w`
+    #[inline]
+    pub fn flip_h(&mut self, container_w: N) {
+        self.x = container_w - self.x - self.w;
+    }
+
+    /// Flip this rect vertically within a container of height `container_h`
+    #[inline]
+    pub fn flip_v(&mut self, container_h: N) {
+        self.y = container_h - self.y - self.h;
+    }
+
+    /// Flip this rect horizontally within a container of width `container_w`, like
+    /// [`Rect::flip_h`], if it fits within `[0, container_w]`.
+    ///
+    /// The rect is left unchanged on error.
+    pub fn try_flip_h(&mut self, container_w: N) -> Result<(), FlipError>
+    where
+        N: PartialOrd + Zero,
+    {
+        if self.x < N::zero() || self.x + self.w > container_w {
+            return Err(FlipError);
+        }
+        self.flip_h(container_w);
+        Ok(())
+    }
+
+    /// Flip this rect vertically within a container of height `container_h`, like
+    /// [`Rect::flip_v`], if it fits within `[0, container_h]`.
+    ///
+    /// The rect is left unchanged on error.
+    pub fn try_flip_v(&mut self, container_h: N) -> Result<(), FlipError>
+    where
+        N: PartialOrd + Zero,
+    {
+        if self.y < N::zero() || self.y + self.h > container_h {
+            return Err(FlipError);
+        }
+        self.flip_v(container_h);
+        Ok(())
+    }
+
+    /// Move this rect by `dx` horizontally and `dy` vertically
+    #[inline]
+    pub fn translate(&self, dx: N, dy: N) -> Rect<N> {
+        Rect { x: self.x + dx, y: self.y + dy, w: self.w, h: self.h }
+    }
+
+    /// Map this rect from `container` coordinates to the `[0, 1]⨯[0, 1]` unit space
+    /// of `container`
+    #[inline]
+    pub fn to_unit(&self, container: &Rect<N>) -> Rect<N> {
+        Rect {
+            x: (self.x - container.x) / container.w,
+            y: (self.y - container.y) / container.h,
+            w: self.w / container.w,
+            h: self.h / container.h,
+        }
+    }
+
+    /// Map this rect from the `[0, 1]⨯[0, 1]` unit space of `container` to
+    /// `container` coordinates, the inverse of [`Rect::to_unit`]
+    #[inline]
+    pub fn from_unit(&self, container: &Rect<N>) -> Rect<N> {
+        Rect {
+            x: container.x + self.x * container.w,
+            y: container.y + self.y * container.h,
+            w: self.w * container.w,
+            h: self.h * container.h,
+        }
+    }
+
+    /// Map this rect from `container` coordinates to the `[-1, 1]⨯[-1, 1]`
+    /// normalized device coordinates of `container`.
+    ///
+    /// With `flip_y`, the y axis points up as most graphics APIs expect, so the
+    /// top of `container` maps to `1`. The result keeps a positive height and
+    /// `y` stays the smallest coordinate.
+    pub fn to_ndc(&self, container: &Rect<N>, flip_y: bool) -> Rect<N>
+    where
+        N: One,
+    {
+        let two = N::one() + N::one();
+        let unit = self.to_unit(container);
+        let y = if flip_y { N::one() - unit.y - unit.h } else { unit.y };
+        Rect { x: unit.x * two - N::one(), y: y * two - N::one(), w: unit.w * two, h: unit.h * two }
+    }
+}
+
+impl<N> Rect<N>
+where
+    N: NumOps + PartialOrd + Copy,
+{
+    /// Check if the point (`x`, `y`) is inside this rect, far edges excluded
+    #[inline]
+    pub fn contains_point(&self, x: N, y: N) -> bool {
+        x >= self.x && y >= self.y && x < self.max_x() && y < self.max_y()
+    }
+
+    /// Check if `inner` is fully inside this rect, edges included
+    #[inline]
+    pub fn contains_rect(&self, inner: &Rect<N>) -> bool {
+        inner.x >= self.x
+            && inner.y >= self.y
+            && inner.max_x() <= self.max_x()
+            && inner.max_y() <= self.max_y()
+    }
+
+    /// Shrink this rect by `top`, `right`, `bottom` and `left` margins.
+    ///
+    /// The size is clamped to zero if margins are larger than this rect.
+    pub fn inset(&self, top: N, right: N, bottom: N, left: N) -> Rect<N>
+    where
+        N: Zero,
+    {
+        let (w, h) = (self.w - left - right, self.h - top - bottom);
+        Rect {
+            x: self.x + left,
+            y: self.y + top,
+            w: if w > N::zero() { w } else { N::zero() },
+            h: if h > N::zero() { h } else { N::zero() },
+        }
+    }
+
+    /// Check if this rect is at least `min_w` wide and `min_h` tall, so a label fits
+    #[inline]
+    pub fn is_readable(&self, min_w: N, min_h: N) -> bool {
+        self.w >= min_w && self.h >= min_h
+    }
+
+    /// Compute the aspect ratio of this rect, longest side over shortest side
     #[inline]
-    pub fn flip_h(&mut self, container_h: N) {
-        self.x = container_h - self.x - self.w;
+    pub fn aspect_ratio(&self) -> N {
+        if self.w > self.h {
+            self.w / self.h
+        } else {
+            self.h / self.w
+        }
     }
 
-    /// Flip this rect vertically within a container of width `w`
+    /// Compute the largest corner radius of a rounded rect drawn in this rect, half
+    /// its shortest side
     #[inline]
-    pub fn flip_v(&mut self, container_w: N) {
-        self.y = container_w - self.y - self.h;
+    pub fn max_corner_radius(&self) -> N
+    where
+        N: One,
+    {
+        let side = if self.w < self.h { self.w } else { self.h };
+        side / (N::one() + N::one())
+    }
+
+    /// Compute the overlapping area of this rect and `other`, if any
+    pub fn intersection(&self, other: &Rect<N>) -> Option<Rect<N>> {
+        let x0 = if self.x > other.x { self.x } else { other.x };
+        let y0 = if self.y > other.y { self.y } else { other.y };
+        let (x1, y1) = (self.max_x(), self.max_y());
+        let (ox1, oy1) = (other.max_x(), other.max_y());
+        let x1 = if x1 < ox1 { x1 } else { ox1 };
+        let y1 = if y1 < oy1 { y1 } else { oy1 };
+        if x0 < x1 && y0 < y1 {
+            Some(Rect { x: x0, y: y0, w: x1 - x0, h: y1 - y0 })
+        } else {
+            None
+        }
+    }
+
+    /// Compute the smallest rect containing this rect and `other`
+    pub fn union(&self, other: &Rect<N>) -> Rect<N> {
+        let x0 = if self.x < other.x { self.x } else { other.x };
+        let y0 = if self.y < other.y { self.y } else { other.y };
+        let (x1, y1) = (self.max_x(), self.max_y());
+        let (ox1, oy1) = (other.max_x(), other.max_y());
+        let x1 = if x1 > ox1 { x1 } else { ox1 };
+        let y1 = if y1 > oy1 { y1 } else { oy1 };
+        Rect { x: x0, y: y0, w: x1 - x0, h: y1 - y0 }
+    }
+
+    /// Check if this rect and `other` share a whole edge, so their union is a rect
+    pub fn shares_edge(&self, other: &Rect<N>) -> bool {
+        let same_row = self.y == other.y && self.h == other.h;
+        let same_column = self.x == other.x && self.w == other.w;
+        (same_row && (self.x + self.w == other.x || other.x + other.w == self.x))
+            || (same_column && (self.y + self.h == other.y || other.y + other.h == self.y))
+    }
+
+    /// Split this rect into its 4 quadrants: NW, NE, SW and SE.
+    ///
+    /// For odd integer dimensions, the right and bottom quadrants are widened so
+    /// the quadrants tile this rect exactly.
+    pub fn quadrants(&self) -> [Rect<N>; 4]
+    where
+        N: One,
+    {
+        let two = N::one() + N::one();
+        let (w0, h0) = (self.w / two, self.h / two);
+        let (w1, h1) = (self.w - w0, self.h - h0);
+        let (x1, y1) = (self.x + w0, self.y + h0);
+        [
+            Rect { x: self.x, y: self.y, w: w0, h: h0 },
+            Rect { x: x1, y: self.y, w: w1, h: h0 },
+            Rect { x: self.x, y: y1, w: w0, h: h1 },
+            Rect { x: x1, y: y1, w: w1, h: h1 },
+        ]
+    }
+
+    /// Decompose this rect minus `hole` into up to 4 non-overlapping rects.
+    ///
+    /// Pieces are, in order, the full width bands above and below `hole`, then the
+    /// pieces left and right of `hole`. Empty pieces are `None`.
+    pub fn subtract(&self, hole: &Rect<N>) -> [Option<Rect<N>>; 4] {
+        let hole = match self.intersection(hole) {
+            Some(hole) => hole,
+            None => return [Some(*self), None, None, None],
+        };
+        let (x1, y1) = (self.x + self.w, self.y + self.h);
+        let (hx1, hy1) = (hole.x + hole.w, hole.y + hole.h);
+        [
+            if hole.y > self.y { Some(Rect { h: hole.y - self.y, ..*self }) } else { None },
+            if hy1 < y1 { Some(Rect { y: hy1, h: y1 - hy1, ..*self }) } else { None },
+            if hole.x > self.x {
+                Some(Rect { x: self.x, w: hole.x - self.x, ..hole })
+            } else {
+                None
+            },
+            if hx1 < x1 { Some(Rect { x: hx1, w: x1 - hx1, ..hole }) } else { None },
+        ]
     }
 }
 
@@ -100,11 +380,27 @@ where
 ///
 /// __Complexity__: `O(1)`
 fn ratio<N>(side_squared: N, size_total: N, size_item: N) -> (N, N)
+where
+    N: NumOps + PartialOrd + One + Copy,
+{
+    ratio_weighted(side_squared, size_total, size_item, (N::one(), N::one()))
+}
+
+/// Compute the ratio (numer / denom) of an item like [`ratio`], but relative to a
+/// target aspect ratio.
+///
+/// `weights` is the target ratio of the strip thickness over the item length along
+/// the strip, as `(length, thickness)`. `(1, 1)` targets square items.
+///
+/// __Complexity__: `O(1)`
+fn ratio_weighted<N>(side_squared: N, size_total: N, size_item: N, weights: (N, N)) -> (N, N)
 where
     N: NumOps + PartialOrd + Copy,
 {
-    let a = size_total * size_total;
-    let b = side_squared * size_item;
+    #[cfg(feature = "count-ops")]
+    count_ops::count_ratio();
+    let a = size_total * size_total * weights.0;
+    let b = side_squared * size_item * weights.1;
     if a >= b {
         (a, b)
     } else {
@@ -121,82 +417,163 @@ where
     S: Fn(&T) -> N,
 {
     let rect_size = rect.w * rect.h;
-    let size_total = items.iter().map(|x| f_item_size(x)).sum();
-    let scale = rect_size / size_total;
-    scale
+    let size_total: N = items.iter().map(f_item_size).sum();
+    rect_size / size_total
+}
+
+/// Distribute zero-area rects at the origin of `rect` if it has no area,
+/// so scaling item sizes doesn't divide by zero.
+///
+/// Returns `true` if items were distributed.
+///
+/// __Complexity__: `O(items.len())`
+fn distribute_zero_area<N, T, R>(rect: Rect<N>, items: &mut [T], f_item_set_rect: &mut R) -> bool
+where
+    N: NumOps + Zero + Copy,
+    R: FnMut(&mut T, Rect<N>),
+{
+    if !(rect.w * rect.h).is_zero() {
+        return false;
+    }
+    let rect_zero = Rect { w: N::zero(), h: N::zero(), ..rect };
+    for item in items {
+        f_item_set_rect(item, rect_zero);
+    }
+    true
+}
+
+/// Distribute the whole `rect` to the item if there is a single one, so its tile is
+/// exactly `rect` without scaling its size.
+///
+/// A single zero-size item gets a zero-area rect at the origin of `rect`, like
+/// zero-size items among others.
+///
+/// Returns `true` if the item was distributed.
+///
+/// __Complexity__: `O(1)`
+fn distribute_single<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: &S,
+    f_item_set_rect: &mut R,
+) -> bool
+where
+    N: Zero + Copy,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    match items {
+        [item] if f_item_size(item).is_zero() => {
+            f_item_set_rect(item, Rect { w: N::zero(), h: N::zero(), ..rect });
+            true
+        }
+        [item] => {
+            f_item_set_rect(item, rect);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Direction along which items are stacked by [`fill`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    /// Stack items from top to bottom, like [`slice`]
+    Vertical,
+    /// Stack items from left to right, like [`dice`]
+    Horizontal,
 }
 
-/// Distribute `items` inside `rect` vertically without checking if they fit perfectly.
+/// Distribute `items` inside `rect` along `axis` without checking if they fit perfectly.
 ///
 /// - `f_item_size` provide the size of an item
 /// - `f_item_set_rect` receive the item distributed Rect.
 ///   Called once for each item and in a stable order.
 ///
 /// __Complexity__: `O(items.len())`
-fn _slice<N, T, S, R>(rect: Rect<N>, items: &mut [T], f_item_size: S, mut f_item_set_rect: R)
-where
+fn _fill<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    axis: Axis,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
     N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
     S: Fn(&T) -> N,
     R: FnMut(&mut T, Rect<N>),
 {
-    let mut y = rect.y;
+    let mut pos = match axis {
+        Axis::Vertical => rect.y,
+        Axis::Horizontal => rect.x,
+    };
     let mut it = items.iter_mut();
     while let Some(item) = it.next() {
         let size_item = f_item_size(item);
-        let rect_item = Rect {
-            x: rect.x,
-            y,
-            w: rect.w,
-            h: if it.len() > 0 { size_item / rect.w } else { rect.h - (y - rect.y) },
+        let rect_item = match axis {
+            Axis::Vertical => Rect {
+                y: pos,
+                h: if it.len() > 0 { size_item / rect.w } else { rect.h - (pos - rect.y) },
+                ..rect
+            },
+            Axis::Horizontal => Rect {
+                x: pos,
+                w: if it.len() > 0 { size_item / rect.h } else { rect.w - (pos - rect.x) },
+                ..rect
+            },
+        };
+        pos += match axis {
+            Axis::Vertical => rect_item.h,
+            Axis::Horizontal => rect_item.w,
         };
-        y += rect_item.h;
         f_item_set_rect(item, rect_item);
     }
 }
 
-/// Distribute `items` inside `rect` vertically.
+/// Distribute `items` inside `rect` along `axis`.
 ///
 /// - `f_item_size` provide the size of an item
 /// - `f_item_set_rect` receive the item distributed Rect.
 ///   Called once for each item and in a stable order.
 ///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
 /// __Complexity__: `O(2⨯items.len())`
-pub fn slice<N, T, S, R>(rect: Rect<N>, items: &mut [T], f_item_size: S, f_item_set_rect: R)
-where
+pub fn fill<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    axis: Axis,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
     N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
     S: Fn(&T) -> N,
     R: FnMut(&mut T, Rect<N>),
 {
+    if distribute_zero_area(rect, items, &mut f_item_set_rect)
+        || distribute_single(rect, items, &f_item_size, &mut f_item_set_rect)
+    {
+        return;
+    }
     let scale = scale(rect, items, &f_item_size);
-    _slice(rect, items, |item| f_item_size(item) * scale, f_item_set_rect);
+    _fill(rect, items, axis, |item| f_item_size(item) * scale, f_item_set_rect);
 }
 
-/// Distribute `items` inside `rect` horizontally without checking if they fit perfectly.
+/// Distribute `items` inside `rect` vertically.
 ///
 /// - `f_item_size` provide the size of an item
 /// - `f_item_set_rect` receive the item distributed Rect.
 ///   Called once for each item and in a stable order.
 ///
-/// __Complexity__: `O(items.len())`
-fn _dice<N, T, S, R>(rect: Rect<N>, items: &mut [T], f_item_size: S, mut f_item_set_rect: R)
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// __Complexity__: `O(2⨯items.len())`
+pub fn slice<N, T, S, R>(rect: Rect<N>, items: &mut [T], f_item_size: S, f_item_set_rect: R)
 where
-    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
     S: Fn(&T) -> N,
     R: FnMut(&mut T, Rect<N>),
 {
-    let mut x = rect.x;
-    let mut it = items.iter_mut();
-    while let Some(item) = it.next() {
-        let size_item = f_item_size(item);
-        let rect_item = Rect {
-            x,
-            y: rect.y,
-            w: if it.len() > 0 { size_item / rect.h } else { rect.w - (x - rect.x) },
-            h: rect.h,
-        };
-        x += rect_item.w;
-        f_item_set_rect(item, rect_item);
-    }
+    fill(rect, items, Axis::Vertical, f_item_size, f_item_set_rect);
 }
 
 /// Distribute `items` inside `rect` horizontally.
@@ -205,6 +582,8 @@ where
 /// - `f_item_set_rect` receive the item distributed Rect.
 ///   Called once for each item and in a stable order.
 ///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
 /// __Complexity__: `O(2⨯items.len())`
 pub fn dice<N, T, S, R>(rect: Rect<N>, items: &mut [T], f_item_size: S, f_item_set_rect: R)
 where
@@ -212,28 +591,26 @@ where
     S: Fn(&T) -> N,
     R: FnMut(&mut T, Rect<N>),
 {
-    let scale = scale(rect, items, &f_item_size);
-    _dice(rect, items, |item| f_item_size(item) * scale, f_item_set_rect);
+    fill(rect, items, Axis::Horizontal, f_item_size, f_item_set_rect);
 }
 
-fn _binary<N, T, R>(
+/// Split `rect` of items with prefix `sums` in 2 areas close to the same sizes,
+/// `offset` being the size before the first item and `value` the total size.
+/// The areas are side by side if `is_wide`, stacked otherwise.
+///
+/// Returns the number of items on the left, their total size and the left and right rects.
+///
+/// __Complexity__: `O(log_2(sums.len()))`
+fn binary_split<N>(
     rect: Rect<N>,
-    items: &mut [T],
-    f_item_set_rect: &mut R,
+    is_wide: bool,
     sums: &[N],
     offset: N,
     value: N,
-) where
-    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
-    R: FnMut(&mut T, Rect<N>),
+) -> (usize, N, Rect<N>, Rect<N>)
+where
+    N: NumOps + PartialOrd + One + Copy,
 {
-    if items.is_empty() || value.is_zero() {
-        return;
-    } else if items.len() == 1 {
-        f_item_set_rect(&mut items[0], rect);
-        return;
-    }
-
     let target = value / (N::one() + N::one()) + offset;
     let mid = sums
         .binary_search_by(|&p| if p > target { Ordering::Greater } else { Ordering::Less })
@@ -241,7 +618,7 @@ fn _binary<N, T, R>(
     debug_assert!(mid > 0);
     let left = sums[mid - 1] - offset;
     let right = value - left;
-    let (lrect, rrect) = if rect.w > rect.h {
+    let (lrect, rrect) = if is_wide {
         let xe = rect.x + rect.w;
         let xm = (rect.x * right + xe * left) / value;
         (Rect { w: xm - rect.x, ..rect }, Rect { x: xm, w: xe - xm, ..rect })
@@ -250,17 +627,71 @@ fn _binary<N, T, R>(
         let ym = (rect.y * right + ye * left) / value;
         (Rect { h: ym - rect.y, ..rect }, Rect { y: ym, h: ye - ym, ..rect })
     };
+    (mid, left, lrect, rrect)
+}
+
+/// Cost of distributing a group of consecutive items inside a rect, an algorithm
+/// choosing the lowest one among equally good choices, see [`reflow`].
+type TieCost<'a, T, N> = Option<&'a dyn Fn(&[T], Rect<N>) -> N>;
+
+fn _binary<N, T, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_set_rect: &mut R,
+    sums: &[N],
+    (offset, value): (N, N),
+    f_tie_cost: TieCost<T, N>,
+    stats: &mut LayoutStats,
+) -> usize
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    R: FnMut(&mut T, Rect<N>),
+{
+    stats.calls += 1;
+    if value.is_zero() {
+        // Don't divide by a zero subtree size, its items have no area
+        let rect_zero = Rect { w: N::zero(), h: N::zero(), ..rect };
+        for item in items {
+            f_item_set_rect(item, rect_zero);
+        }
+        return 1;
+    } else if items.is_empty() {
+        return 1;
+    } else if items.len() == 1 {
+        f_item_set_rect(&mut items[0], rect);
+        return 1;
+    }
+
+    let mut is_wide = rect.w > rect.h;
+    if let Some(f_cost) = f_tie_cost {
+        // A square rect is split as well side by side as stacked
+        if rect.w == rect.h {
+            let cost = |is_wide| {
+                let (mid, _, lrect, rrect) = binary_split(rect, is_wide, sums, offset, value);
+                f_cost(&items[..mid], lrect) + f_cost(&items[mid..], rrect)
+            };
+            is_wide = cost(true) < cost(false);
+        }
+    }
+    let (mid, left, lrect, rrect) = binary_split(rect, is_wide, sums, offset, value);
+    let right = value - left;
+    let mut depth = 0;
     if mid == 1 {
         f_item_set_rect(&mut items[0], lrect);
     } else {
-        _binary(lrect, &mut items[0..mid], f_item_set_rect, &sums[0..mid], offset, left);
+        let (litems, lsums) = (&mut items[0..mid], &sums[0..mid]);
+        let lspan = (offset, left);
+        depth = _binary(lrect, litems, f_item_set_rect, lsums, lspan, f_tie_cost, stats);
     }
     let ritems = &mut items[mid..];
     if ritems.len() == 1 {
         f_item_set_rect(&mut ritems[0], rrect);
     } else if !ritems.is_empty() {
-        _binary(rrect, ritems, f_item_set_rect, &sums[mid..], sums[mid - 1], right);
+        let (rsums, rspan) = (&sums[mid..], (sums[mid - 1], right));
+        let rdepth = _binary(rrect, ritems, f_item_set_rect, rsums, rspan, f_tie_cost, stats);
+        depth = depth.max(rdepth);
     }
+    1 + depth
 }
 
 /// Distribute `items` inside `rect` by splitting it recursively in 2 areas close to the same sizes.
@@ -271,14 +702,65 @@ fn _binary<N, T, R>(
 ///
 /// To maximize the output quality its best to sort items by size in descending order.
 ///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
 /// __Complexity__: `O(3⨯items.len()⨯log_2(items.len()))`
-pub fn binary<N, T, S, R>(rect: Rect<N>, items: &mut [T], f_item_size: S, mut f_item_set_rect: R)
+pub fn binary<N, T, S, R>(rect: Rect<N>, items: &mut [T], f_item_size: S, f_item_set_rect: R)
 where
     N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
     S: Fn(&T) -> N,
     R: FnMut(&mut T, Rect<N>),
 {
-    if !items.is_empty() {
+    binary_with_stats(rect, items, f_item_size, f_item_set_rect);
+}
+
+/// Recursion statistics of a layout, see [`binary_with_stats`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LayoutStats {
+    /// Number of nested invocations of the recursive layout function along the
+    /// deepest path, the top-level call included: `1` if it didn't recurse, `0` if it
+    /// wasn't invoked, like for a single item
+    pub max_depth: usize,
+    /// Total number of invocations of the recursive layout function, the top-level
+    /// call included
+    pub calls: usize,
+}
+
+/// Distribute `items` inside `rect` like [`binary`], returning the recursion statistics.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// __Complexity__: `O(3⨯items.len()⨯log_2(items.len()))`
+pub fn binary_with_stats<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_set_rect: R,
+) -> LayoutStats
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    _binary_with_stats(rect, items, f_item_size, f_item_set_rect, None)
+}
+
+fn _binary_with_stats<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    mut f_item_set_rect: R,
+    f_tie_cost: TieCost<T, N>,
+) -> LayoutStats
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let mut stats = LayoutStats::default();
+    if !items.is_empty() && !distribute_single(rect, items, &f_item_size, &mut f_item_set_rect) {
         let mut size_total = N::zero();
         let sums: Vec<N> = items
             .iter()
@@ -288,259 +770,4793 @@ where
                 size_total
             })
             .collect();
-        _binary(rect, items, &mut f_item_set_rect, sums.as_slice(), N::zero(), size_total);
+        let (sums, span) = (sums.as_slice(), (N::zero(), size_total));
+        let f_item_set_rect = &mut f_item_set_rect;
+        stats.max_depth = _binary(rect, items, f_item_set_rect, sums, span, f_tie_cost, &mut stats);
     }
+    stats
 }
 
-/// Distribute `items` inside `rect` while trying to get the aspect ratio as close
-/// to 1 as possible without checking is they fit.
+/// Find the number of items of the next strip along `side`, stopping before the item
+/// that would make the strip aspect ratio worse.
 ///
-/// - `f_item_size` provide the size of an item
-/// - `f_item_set_rect` receive the item distributed Rect.
-///   Called once for each item and in a stable order.
+/// Returns the number of items in the strip and their total size.
 ///
-/// __Complexity__: `O(2⨯items.len())`
-fn _squarify<N, T, S, R>(
-    mut rect: Rect<N>,
-    mut items: &mut [T],
-    f_item_size: S,
-    mut f_item_set_rect: R,
-) where
+/// __Complexity__: `O(strip length)`
+fn squarify_strip<N, T, S>(side: N, items: &[T], f_item_size: &S) -> (usize, N)
+where
     N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
     S: Fn(&T) -> N,
-    R: FnMut(&mut T, Rect<N>),
 {
-    while !items.is_empty() {
-        let is_wide = rect.w > rect.h;
-        let side = if is_wide { rect.h } else { rect.w };
-        let mut split_side = if is_wide { rect.w } else { rect.h };
-        let side_squared = side * side;
-        let mut size_total0 = N::zero();
-        let (mut numer0, mut denom0) = (N::one(), N::zero());
-        let split_idx = items
-            .iter()
-            .position(|item| {
-                let size_item = f_item_size(item);
-                let size_total1 = size_total0 + size_item;
+    squarify_strip_weighted(side, items, f_item_size, (N::one(), N::one()), N::zero())
+}
 
-                let (numer1, denom1) = ratio(side_squared, size_total1, size_item);
-                let worse = numer1 * denom0 > numer0 * denom1;
-                if worse {
-                    split_side = size_total0 / side;
-                }
-                size_total0 = size_total1;
-                numer0 = numer1;
-                denom0 = denom1;
+/// Find the number of items of the next strip along `side` like [`squarify_strip`],
+/// but relative to a target aspect ratio, see [`ratio_weighted`].
+///
+/// The strip only stops if the cross-multiplied ratios are worse by more than `epsilon`.
+///
+/// Returns the number of items in the strip and their total size.
+///
+/// __Complexity__: `O(strip length)`
+fn squarify_strip_weighted<N, T, S>(
+    side: N,
+    items: &[T],
+    f_item_size: &S,
+    weights: (N, N),
+    epsilon: N,
+) -> (usize, N)
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+{
+    let side_squared = side * side;
+    let mut size_total0 = N::zero();
+    let (mut numer0, mut denom0) = (N::one(), N::zero());
+    for (idx, item) in items.iter().enumerate() {
+        let size_item = f_item_size(item);
+        let size_total1 = size_total0 + size_item;
 
-                worse
-            })
-            .unwrap_or(items.len());
-        let (head, tail) = items.split_at_mut(split_idx);
-        items = tail;
-        if is_wide {
-            let w = rect.w - split_side;
-            rect.w = split_side;
-            _slice(rect, head, &f_item_size, &mut f_item_set_rect);
-            rect.w = w;
-            rect.x += split_side;
+        let (numer1, denom1) = ratio_weighted(side_squared, size_total1, size_item, weights);
+        let worse = numer1 * denom0 > numer0 * denom1 + epsilon;
+        if worse {
+            return (idx, size_total0);
+        }
+        size_total0 = size_total1;
+        numer0 = numer1;
+        denom0 = denom1;
+    }
+    (items.len(), size_total0)
+}
+
+/// Distribute `items` inside `rect` along `axis` like `_fill`, but each tile length
+/// only depends on its item size: the last item doesn't absorb the remaining extent,
+/// so items may leave some of `rect` uncovered or overflow it, see [`fill_fit`].
+///
+/// __Complexity__: `O(items.len())`
+fn _fill_fit<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    axis: Axis,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let is_vertical = axis == Axis::Vertical;
+    let mut pos = if is_vertical { rect.y } else { rect.x };
+    for item in items {
+        let size_item = f_item_size(item);
+        let rect_item = if is_vertical {
+            Rect { y: pos, h: size_item / rect.w, ..rect }
+        } else {
+            Rect { x: pos, w: size_item / rect.h, ..rect }
+        };
+        pos += if is_vertical { rect_item.h } else { rect_item.w };
+        f_item_set_rect(item, rect_item);
+    }
+}
+
+/// Distribute `items` inside `rect` along `axis` like `_fill`, but the trailing run
+/// of equal-sized items shares the remaining extent evenly instead of its last item
+/// absorbing it, so they get the exact same tile dimensions, see
+/// [`SquarifyOptions::equal_tiles`].
+///
+/// __Complexity__: `O(2⨯items.len())`
+fn _fill_equal_run<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    axis: Axis,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let is_vertical = axis == Axis::Vertical;
+    let size_last = match items.last() {
+        Some(item) => f_item_size(item),
+        None => return,
+    };
+    let run_start =
+        items.iter().rposition(|item| f_item_size(item) != size_last).map_or(0, |idx| idx + 1);
+    let mut run_len = N::zero();
+    for _ in run_start..items.len() {
+        run_len += N::one();
+    }
+    let (start, extent, thickness) =
+        if is_vertical { (rect.y, rect.h, rect.w) } else { (rect.x, rect.w, rect.h) };
+    let mut pos = start;
+    let mut run_length = None;
+    for (idx, item) in items.iter_mut().enumerate() {
+        let length = if idx < run_start {
+            f_item_size(item) / thickness
+        } else {
+            *run_length.get_or_insert_with(|| (start + extent - pos) / run_len)
+        };
+        let rect_item = if is_vertical {
+            Rect { y: pos, h: length, ..rect }
         } else {
-            let h = rect.h - split_side;
-            rect.h = split_side;
-            _dice(rect, head, &f_item_size, &mut f_item_set_rect);
-            rect.h = h;
-            rect.y += split_side;
+            Rect { x: pos, w: length, ..rect }
         };
+        pos += length;
+        f_item_set_rect(item, rect_item);
+    }
+}
+
+/// Distribute the first `strip.0` items of total size `strip.1` as a strip at the start
+/// of `rect` and shrink `rect` to the remaining area.
+///
+/// The strip takes the whole `rect` if there is no more items after it, unless
+/// `options` don't absorb the leftover area.
+///
+/// Returns the remaining items.
+///
+/// __Complexity__: `O(strip.0)`
+fn squarify_place_strip<'a, N, T, S, R>(
+    rect: &mut Rect<N>,
+    is_wide: bool,
+    items: &'a mut [T],
+    (split_idx, size_strip): (usize, N),
+    options: &SquarifyOptions<N>,
+    f_item_size: &S,
+    f_item_set_rect: &mut R,
+) -> &'a mut [T]
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let is_last = split_idx == items.len() && options.absorb_leftover;
+    let (head, tail) = items.split_at_mut(split_idx);
+    let rect_strip = squarify_split_strip(rect, is_wide, size_strip, is_last);
+    let axis = if is_wide { Axis::Vertical } else { Axis::Horizontal };
+    if options.equal_tiles {
+        _fill_equal_run(rect_strip, head, axis, f_item_size, &mut *f_item_set_rect);
+    } else {
+        _fill(rect_strip, head, axis, f_item_size, &mut *f_item_set_rect);
+    }
+    tail
+}
+
+/// Split a strip of total size `size_strip` at the start of `rect` and shrink `rect`
+/// to the remaining area.
+///
+/// The strip takes the whole `rect` if it is the last one.
+///
+/// Returns the strip Rect.
+///
+/// __Complexity__: `O(1)`
+fn squarify_split_strip<N>(
+    rect: &mut Rect<N>,
+    is_wide: bool,
+    size_strip: N,
+    is_last: bool,
+) -> Rect<N>
+where
+    N: NumAssignOps + NumOps + Copy,
+{
+    let mut rect_strip = *rect;
+    if is_wide {
+        let split_side = if is_last { rect.w } else { size_strip / rect.h };
+        rect_strip.w = split_side;
+        rect.w -= split_side;
+        rect.x += split_side;
+    } else {
+        let split_side = if is_last { rect.h } else { size_strip / rect.w };
+        rect_strip.h = split_side;
+        rect.h -= split_side;
+        rect.y += split_side;
     }
+    rect_strip
 }
 
 /// Distribute `items` inside `rect` while trying to get the aspect ratio as close
-/// to 1 as possible.
+/// to 1 as possible without checking is they fit.
 ///
 /// - `f_item_size` provide the size of an item
 /// - `f_item_set_rect` receive the item distributed Rect.
 ///   Called once for each item and in a stable order.
 ///
-/// To maximize the output quality its best to sort items by size in descending order.
+/// Returns the area of `rect` left after the last strip, empty unless the last strip
+/// doesn't absorb the remaining extent, see [`squarify_fit`].
 ///
-/// __Complexity__: `O(3⨯items.len())`
-pub fn squarify<N, T, S, R>(rect: Rect<N>, items: &mut [T], f_item_size: S, f_item_set_rect: R)
+/// __Complexity__: `O(2⨯items.len())`
+fn _squarify<N, T, S, R>(
+    mut rect: Rect<N>,
+    mut items: &mut [T],
+    options: &SquarifyOptions<N>,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+    f_tie_cost: TieCost<T, N>,
+) -> Rect<N>
 where
-    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
     S: Fn(&T) -> N,
     R: FnMut(&mut T, Rect<N>),
 {
-    let scale = scale(rect, items, &f_item_size);
-    _squarify(rect, items, |item| f_item_size(item) * scale, f_item_set_rect);
+    let square_wide = options.square_prefers == Axis::Vertical;
+    let wide = |rect: &Rect<N>| rect.w > rect.h || (rect.w == rect.h && square_wide);
+    let is_first_wide = wide(&rect);
+    while !items.is_empty() {
+        let is_wide = if options.strip_order_stable {
+            false
+        } else if options.pin_orientation {
+            is_first_wide
+        } else {
+            wide(&rect)
+        };
+        let side = if is_wide { rect.h } else { rect.w };
+        let weights = (N::one(), N::one());
+        let strip = squarify_strip_weighted(side, items, &f_item_size, weights, options.epsilon);
+        let (is_wide, strip) = match f_tie_cost {
+            Some(f_cost) => {
+                // The orientation only follows the shape of the remaining area
+                let can_flip = !options.strip_order_stable && !options.pin_orientation;
+                let choice = (is_wide, strip);
+                squarify_break_ties(rect, items, choice, can_flip, options, &f_item_size, f_cost)
+            }
+            None => (is_wide, strip),
+        };
+        items = squarify_place_strip(
+            &mut rect,
+            is_wide,
+            items,
+            strip,
+            options,
+            &f_item_size,
+            &mut f_item_set_rect,
+        );
+    }
+    rect
 }
 
-fn _ordered_pivot<N, T, S, R, P>(
-    mut rect: Rect<N>,
+/// Break the ties of a squarified `strip` towards the lowest `f_cost`: the strip
+/// orientation in a square `rect` if `can_flip`, and the strip without its last item
+/// if that item doesn't make the strip aspect ratio better.
+///
+/// The tiles of the strip are costed one by one, the remaining items as a group.
+///
+/// Returns the orientation and the strip to place.
+///
+/// __Complexity__: `O(4⨯items.len())`
+fn squarify_break_ties<N, T, S>(
+    rect: Rect<N>,
     items: &mut [T],
+    (is_wide, strip): (bool, (usize, N)),
+    can_flip: bool,
+    options: &SquarifyOptions<N>,
     f_item_size: &S,
-    f_item_set_rect: &mut R,
-    f_pivot: &P,
-) where
+    f_cost: &dyn Fn(&[T], Rect<N>) -> N,
+) -> (bool, (usize, N))
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+{
+    let (len, size_strip) = strip;
+    let mut shorter = None;
+    if len > 1 {
+        let side = if is_wide { rect.h } else { rect.w };
+        let side_squared = side * side;
+        let weights = (N::one(), N::one());
+        let size_item = f_item_size(&items[len - 1]);
+        let size_shorter = size_strip - size_item;
+        let size_item0 = f_item_size(&items[len - 2]);
+        let (numer0, denom0) = ratio_weighted(side_squared, size_shorter, size_item0, weights);
+        let (numer1, denom1) = ratio_weighted(side_squared, size_strip, size_item, weights);
+        let better = numer1 * denom0 + options.epsilon < numer0 * denom1;
+        if !better {
+            shorter = Some((len - 1, size_shorter));
+        }
+    }
+    let orientations = if can_flip && rect.w == rect.h { 2 } else { 1 };
+
+    let mut best = (is_wide, strip);
+    let mut cost_best = None;
+    let mut rects = Vec::with_capacity(len);
+    for is_wide in [is_wide, !is_wide].iter().copied().take(orientations) {
+        for &strip in [Some(strip), shorter].iter().flatten() {
+            rects.clear();
+            let mut rect_left = rect;
+            let f_push = &mut |_: &mut T, r| rects.push(r);
+            squarify_place_strip(
+                &mut rect_left,
+                is_wide,
+                items,
+                strip,
+                options,
+                f_item_size,
+                f_push,
+            );
+            let (head, tail) = items.split_at(strip.0);
+            let mut cost = f_cost(tail, rect_left);
+            for (idx, &r) in rects.iter().enumerate() {
+                cost += f_cost(&head[idx..idx + 1], r);
+            }
+            if cost_best.is_none_or(|cost_best| cost < cost_best) {
+                best = (is_wide, strip);
+                cost_best = Some(cost);
+            }
+        }
+    }
+    best
+}
+
+/// Distribute `items` inside `rect` while trying to get the aspect ratio as close
+/// to 1 as possible.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// To maximize the output quality its best to sort items by size in descending order.
+///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify<N, T, S, R>(rect: Rect<N>, items: &mut [T], f_item_size: S, f_item_set_rect: R)
+where
     N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
     S: Fn(&T) -> N,
     R: FnMut(&mut T, Rect<N>),
-    P: Fn(&[T]) -> usize,
 {
-    let p0_idx = f_pivot(items);
-    let (l1, lrem) = items.split_at_mut(p0_idx);
+    squarify_with(rect, items, &SquarifyOptions::new(), f_item_size, f_item_set_rect);
+}
 
-    let is_wide = rect.w >= rect.h;
-    let side = if is_wide { rect.h } else { rect.w };
-    let side_squared = side * side;
+/// Options of the squarified algorithm, see [`squarify_with`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SquarifyOptions<N> {
+    strip_order_stable: bool,
+    equal_tiles: bool,
+    pin_orientation: bool,
+    clamp_non_negative: bool,
+    square_prefers: Axis,
+    epsilon: N,
+    /// Whether the last strip takes the whole remaining area, only disabled by
+    /// layouts that report it, like [`squarify_fit`]
+    absorb_leftover: bool,
+}
 
-    if !l1.is_empty() {
-        let l1_size = l1.iter().map(|x| f_item_size(x)).sum::<N>();
-        let r1_oside = l1_size / side;
-        let r1;
-        if is_wide {
-            r1 = Rect { w: r1_oside, ..rect };
-            rect.x += r1_oside;
-            rect.w -= r1_oside;
-        } else {
-            r1 = Rect { h: r1_oside, ..rect };
-            rect.y += r1_oside;
-            rect.h -= r1_oside;
-        }
-        if l1.len() == 1 {
-            f_item_set_rect(&mut l1[0], r1);
-        } else {
-            _ordered_pivot(r1, l1, f_item_size, f_item_set_rect, f_pivot);
+impl<N: Zero> Default for SquarifyOptions<N> {
+    fn default() -> Self {
+        SquarifyOptions {
+            strip_order_stable: false,
+            equal_tiles: false,
+            pin_orientation: false,
+            clamp_non_negative: false,
+            square_prefers: Axis::Horizontal,
+            epsilon: N::zero(),
+            absorb_leftover: true,
         }
     }
+}
 
-    let (p, lrem) = lrem.split_first_mut().unwrap();
-    let p_size = f_item_size(p);
-    if lrem.is_empty() {
-        f_item_set_rect(p, rect);
-    } else {
-        let mut t_size = p_size;
-        let mut p1_idx = 0;
-        let mut pl2_size = t_size;
-        let (mut numer_b, mut denom_b) = (N::one(), N::zero());
-        for idx in 0..lrem.len() {
-            let size_item = f_item_size(&lrem[idx]);
-            t_size += size_item;
-            let (numer, denom) = ratio(side_squared, t_size, size_item);
-            let better_ratio = numer * denom_b < numer_b * denom;
-            if better_ratio {
-                numer_b = numer;
-                denom_b = denom;
-                p1_idx = idx;
-                pl2_size = t_size;
-            }
-        }
-        let (l2, l3) = lrem.split_at_mut(p1_idx + 1);
-        let pr2_oside = pl2_size / side;
-        let p_side = p_size / pr2_oside;
-        let rp;
-        let r2;
-        let r3;
-        if is_wide {
-            rp = Rect { w: pr2_oside, h: p_side, ..rect };
-            r2 = Rect { w: pr2_oside, y: rect.y + p_side, h: rect.h - p_side, ..rect };
-            r3 = Rect { x: rect.x + pr2_oside, w: rect.w - pr2_oside, ..rect };
-        } else {
-            rp = Rect { h: pr2_oside, w: p_side, ..rect };
-            r2 = Rect { h: pr2_oside, x: rect.x + p_side, w: rect.w - p_side, ..rect };
-            r3 = Rect { y: rect.y + pr2_oside, h: rect.h - pr2_oside, ..rect };
-        }
-        f_item_set_rect(p, rp);
-        if l2.len() == 1 {
-            f_item_set_rect(&mut l2[0], r2);
-        } else if !l2.is_empty() {
-            _ordered_pivot(r2, l2, f_item_size, f_item_set_rect, f_pivot);
-        }
-        if l3.len() == 1 {
-            f_item_set_rect(&mut l3[0], r3);
-        } else if !l3.is_empty() {
-            _ordered_pivot(r3, l3, f_item_size, f_item_set_rect, f_pivot);
-        }
+impl<N: Zero> SquarifyOptions<N> {
+    /// Options matching [`squarify`] behavior
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Always lay out strips as rows from top to bottom.
+    ///
+    /// Items inside a strip are always distributed in input order, but strips are
+    /// either columns or rows depending on the remaining area, so reading tiles by
+    /// their `(y, x)` origin doesn't follow the input order.
+    /// With this option it does, at the cost of worse aspect ratios.
+    pub fn strip_order_stable(mut self, strip_order_stable: bool) -> Self {
+        self.strip_order_stable = strip_order_stable;
+        self
+    }
+
+    /// Give the exact same dimensions to a run of equal-sized items in a strip.
+    ///
+    /// The last item of each strip absorbs rounding errors, so equal-sized items can
+    /// end up with slightly different tiles. With this option, the trailing run of
+    /// equal-sized items of each strip divides the rest of the strip evenly for a
+    /// cleaner grid look.
+    pub fn equal_tiles(mut self, equal_tiles: bool) -> Self {
+        self.equal_tiles = equal_tiles;
+        self
+    }
+
+    /// Lay out all strips with the orientation of the first one.
+    ///
+    /// By default, each strip is a column if the remaining area is wider than tall
+    /// and a row otherwise, so orientation changes as strips are placed.
+    pub fn pin_orientation(mut self, pin_orientation: bool) -> Self {
+        self.pin_orientation = pin_orientation;
+        self
+    }
+
+    /// Clamp slightly negative tile widths and heights of [`squarify_with`] to zero.
+    ///
+    /// Rounding errors of the last tile of a strip, which absorbs the remaining
+    /// extent, can produce tiny negative dimensions for near zero sized items.
+    /// With this option, squarified tiles never have negative dimensions. Only the
+    /// squarified layouts take these options, other layouts like [`binary`] or
+    /// [`dice`] aren't clamped.
+    pub fn clamp_non_negative(mut self, clamp_non_negative: bool) -> Self {
+        self.clamp_non_negative = clamp_non_negative;
+        self
+    }
+
+    /// Axis along which the items of a strip are stacked when the remaining area is
+    /// exactly square.
+    ///
+    /// Defaults to [`Axis::Horizontal`]: a square area is treated as tall, so the strip
+    /// is a row at its top. [`Axis::Vertical`] makes it a column at its left.
+    pub fn square_prefers(mut self, square_prefers: Axis) -> Self {
+        self.square_prefers = square_prefers;
+        self
+    }
+
+    /// Treat near ties of strip aspect ratios as not worse.
+    ///
+    /// A strip is extended with the next item unless its cross-multiplied aspect
+    /// ratios get worse by more than `epsilon`, which keeps the grouping of near-equal
+    /// items stable across tiny data changes. Defaults to 0, the exact comparison.
+    ///
+    /// The compared products are in the fourth power of area units, once item sizes
+    /// are scaled to the `rect` area: an absolute `epsilon` is much looser for a
+    /// small `rect` than for a large one, scale it with `(rect.w ⨯ rect.h)^4`.
+    pub fn epsilon(mut self, epsilon: N) -> Self {
+        self.epsilon = epsilon;
+        self
     }
 }
 
-/// Distribute `items` inside `rect` by splitting it recursively around pivot by middle in 4 areas
+/// Distribute `items` inside `rect` while trying to get the aspect ratio as close
+/// to 1 as possible, using custom `options`.
 ///
 /// - `f_item_size` provide the size of an item
 /// - `f_item_set_rect` receive the item distributed Rect.
 ///   Called once for each item and in a stable order.
 ///
-/// __Complexity__: `O(2⨯items.len()⨯log_4(items.len()))`
-pub fn ordered_pivot_by_middle<N, T, S, R>(
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// Returns the leftover area of `rect` after the last strip. Item sizes are scaled
+/// for the last strip to reach the far edge of `rect`, so it has no area, see
+/// [`squarify_fit`] for a genuine remainder.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_with<N, T, S, R>(
     rect: Rect<N>,
     items: &mut [T],
+    options: &SquarifyOptions<N>,
+    f_item_size: S,
+    f_item_set_rect: R,
+) -> Rect<N>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    _squarify_with(rect, items, options, f_item_size, f_item_set_rect, None)
+}
+
+fn _squarify_with<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    options: &SquarifyOptions<N>,
     f_item_size: S,
     mut f_item_set_rect: R,
-) where
+    f_tie_cost: TieCost<T, N>,
+) -> Rect<N>
+where
     N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
     S: Fn(&T) -> N,
     R: FnMut(&mut T, Rect<N>),
 {
-    if !items.is_empty() {
-        let scale = scale(rect, items, &f_item_size);
-        let f_item_size_scaled = |item: &T| f_item_size(item) * scale;
-        let f_pivot = |items: &[T]| items.len() / 2;
-        _ordered_pivot(rect, items, &f_item_size_scaled, &mut f_item_set_rect, &f_pivot)
+    if distribute_zero_area(rect, items, &mut f_item_set_rect)
+        || distribute_single(rect, items, &f_item_size, &mut f_item_set_rect)
+    {
+        return Rect { w: N::zero(), h: N::zero(), ..rect };
     }
+    let scale = scale(rect, items, &f_item_size);
+    let clamp = options.clamp_non_negative;
+    _squarify(
+        rect,
+        items,
+        options,
+        |item| f_item_size(item) * scale,
+        |item, mut r: Rect<N>| {
+            if clamp {
+                if r.w < N::zero() {
+                    r.w = N::zero();
+                }
+                if r.h < N::zero() {
+                    r.h = N::zero();
+                }
+            }
+            f_item_set_rect(item, r)
+        },
+        f_tie_cost,
+    )
 }
 
-/// Distribute `items` inside `rect` by splitting it recursively around pivot by size in 4 areas
+/// Distribute `items` inside `rect` like [`squarify`], but without scaling item sizes
+/// to the `rect` area.
 ///
 /// - `f_item_size` provide the size of an item
 /// - `f_item_set_rect` receive the item distributed Rect.
 ///   Called once for each item and in a stable order.
 ///
-/// __Complexity__: `O(items.len()^2)`
-pub fn ordered_pivot_by_size<N, T, S, R>(
+/// Tiles are only computed from their size, the last strip doesn't absorb the
+/// remaining area. Returns the leftover area of `rect` after the last strip, empty if items fill or
+/// overflow `rect`. Overflowing tiles extend past `rect`.
+///
+/// __Complexity__: `O(2⨯items.len())`
+pub fn squarify_fit<N, T, S, R>(
     rect: Rect<N>,
     items: &mut [T],
     f_item_size: S,
-    mut f_item_set_rect: R,
-) where
-    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    f_item_set_rect: R,
+) -> Rect<N>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
     S: Fn(&T) -> N,
     R: FnMut(&mut T, Rect<N>),
 {
-    if !items.is_empty() {
-        let scale = scale(rect, items, &f_item_size);
-        let f_item_size_scaled = |item: &T| f_item_size(item) * scale;
-        let f_pivot = |items: &[T]| {
-            items
-                .iter()
-                .enumerate()
-                .fold((0usize, N::zero()), |(idx_b, size_b), (idx, item)| {
-                    let size_item = f_item_size(item);
-                    if size_item > size_b {
-                        (idx, size_item)
-                    } else {
-                        (idx_b, size_b)
-                    }
-                })
-                .0
-        };
-        _ordered_pivot(rect, items, &f_item_size_scaled, &mut f_item_set_rect, &f_pivot)
+    let options = SquarifyOptions { absorb_leftover: false, ..SquarifyOptions::new() };
+    let mut leftover = _squarify(rect, items, &options, f_item_size, f_item_set_rect, None);
+    if !(leftover.w > N::zero() && leftover.h > N::zero()) {
+        leftover.w = N::zero();
+        leftover.h = N::zero();
     }
+    leftover
 }
 
-#[cfg(test)]
-mod tests {
-    use std::fmt::{Debug, Display};
-    use std::ops::Mul;
-
-    use num_traits::Signed;
-
-    use super::*;
-
-    fn svg<N: NumOps + Copy + Display>(
-        view_box: Rect<N>,
-        slice: &[(usize, N, Rect<N>)],
-        scale: N,
-    ) -> String {
-        use std::fmt::Write;
-
+fn _ordered_pivot<N, T, S, R, P, Q>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: &S,
+    f_item_set_rect: &mut R,
+    (f_pivot, f_pivot_tied): (&P, &Q),
+    f_tie_cost: TieCost<T, N>,
+    stats: &mut LayoutStats,
+) -> usize
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+    P: Fn(&[T]) -> usize,
+    Q: Fn(&[T], usize, usize) -> bool,
+{
+    stats.calls += 1;
+    let mut depth = 0;
+    let is_wide = rect.w >= rect.h;
+    let p0_idx = f_pivot(items);
+    let (is_wide, p0_idx, p1) = match f_tie_cost {
+        Some(f_cost) => {
+            let choice = (is_wide, p0_idx);
+            let (is_wide, p0_idx, p1) =
+                ordered_pivot_break_ties(rect, items, choice, f_item_size, f_pivot_tied, f_cost);
+            (is_wide, p0_idx, Some(p1))
+        }
+        None => (is_wide, p0_idx, None),
+    };
+    let (l1, lrem) = items.split_at_mut(p0_idx);
+    let (p, lrem) = lrem.split_first_mut().unwrap();
+    let side = if is_wide { rect.h } else { rect.w };
+    let l1_size = if l1.is_empty() { None } else { Some(l1.iter().map(f_item_size).sum::<N>()) };
+    let p_size = f_item_size(p);
+    let (p1_idx, pl2_size) =
+        p1.unwrap_or_else(|| ordered_pivot_p1(side * side, p_size, lrem, f_item_size).0);
+    let pl2_size = if lrem.is_empty() { None } else { Some(pl2_size) };
+    let [r1, rp, r2, r3] = ordered_pivot_rects(rect, is_wide, l1_size, p_size, pl2_size);
+    let f_pivot = (f_pivot, f_pivot_tied);
+
+    if l1.len() == 1 {
+        f_item_set_rect(&mut l1[0], r1);
+    } else if !l1.is_empty() {
+        depth = _ordered_pivot(r1, l1, f_item_size, f_item_set_rect, f_pivot, f_tie_cost, stats);
+    }
+    f_item_set_rect(p, rp);
+    if !lrem.is_empty() {
+        let (l2, l3) = lrem.split_at_mut(p1_idx + 1);
+        if l2.len() == 1 {
+            f_item_set_rect(&mut l2[0], r2);
+        } else if !l2.is_empty() {
+            let depth_2 =
+                _ordered_pivot(r2, l2, f_item_size, f_item_set_rect, f_pivot, f_tie_cost, stats);
+            depth = depth.max(depth_2);
+        }
+        if l3.len() == 1 {
+            f_item_set_rect(&mut l3[0], r3);
+        } else if !l3.is_empty() {
+            let depth_3 =
+                _ordered_pivot(r3, l3, f_item_size, f_item_set_rect, f_pivot, f_tie_cost, stats);
+            depth = depth.max(depth_3);
+        }
+    }
+    1 + depth
+}
+
+/// Find the items next to the pivot of size `p_size` in `_ordered_pivot`: the first
+/// items of `lrem` giving the pivot the best aspect ratio, the first one on ties.
+///
+/// Returns the index of the last of them with the total size of the pivot and them,
+/// and the pivot ratio (numer, denom).
+///
+/// __Complexity__: `O(lrem.len())`
+fn ordered_pivot_p1<N, T, S>(
+    side_squared: N,
+    p_size: N,
+    lrem: &[T],
+    f_item_size: &S,
+) -> ((usize, N), (N, N))
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+{
+    let mut t_size = p_size;
+    let mut p1_idx = 0;
+    let mut pl2_size = t_size;
+    let (mut numer_b, mut denom_b) = (N::one(), N::zero());
+    for (idx, item) in lrem.iter().enumerate() {
+        let size_item = f_item_size(item);
+        t_size += size_item;
+        let (numer, denom) = ratio(side_squared, t_size, size_item);
+        let better_ratio = numer * denom_b < numer_b * denom;
+        if better_ratio {
+            numer_b = numer;
+            denom_b = denom;
+            p1_idx = idx;
+            pl2_size = t_size;
+        }
+    }
+    ((p1_idx, pl2_size), (numer_b, denom_b))
+}
+
+/// Split `rect` like `_ordered_pivot`, `l1_size` being the size of the items before
+/// the pivot if any, `p_size` the pivot size and `pl2_size` the size of the pivot and
+/// the items next to it if any item is after the pivot.
+///
+/// Returns the rects of the items before the pivot, of the pivot, of the items next
+/// to it and of the items after them. Without items after the pivot, it takes the
+/// remaining area and the last 2 rects are this same area.
+///
+/// __Complexity__: `O(1)`
+fn ordered_pivot_rects<N>(
+    mut rect: Rect<N>,
+    is_wide: bool,
+    l1_size: Option<N>,
+    p_size: N,
+    pl2_size: Option<N>,
+) -> [Rect<N>; 4]
+where
+    N: NumAssignOps + NumOps + Copy,
+{
+    let side = if is_wide { rect.h } else { rect.w };
+    let mut r1 = rect;
+    if let Some(l1_size) = l1_size {
+        let r1_oside = l1_size / side;
+        if is_wide {
+            r1 = Rect { w: r1_oside, ..rect };
+            rect.x += r1_oside;
+            rect.w -= r1_oside;
+        } else {
+            r1 = Rect { h: r1_oside, ..rect };
+            rect.y += r1_oside;
+            rect.h -= r1_oside;
+        }
+    }
+
+    let pl2_size = match pl2_size {
+        Some(pl2_size) => pl2_size,
+        None => return [r1, rect, rect, rect],
+    };
+    let pr2_oside = pl2_size / side;
+    let p_side = p_size / pr2_oside;
+    if is_wide {
+        [
+            r1,
+            Rect { w: pr2_oside, h: p_side, ..rect },
+            Rect { w: pr2_oside, y: rect.y + p_side, h: rect.h - p_side, ..rect },
+            Rect { x: rect.x + pr2_oside, w: rect.w - pr2_oside, ..rect },
+        ]
+    } else {
+        [
+            r1,
+            Rect { h: pr2_oside, w: p_side, ..rect },
+            Rect { h: pr2_oside, x: rect.x + p_side, w: rect.w - p_side, ..rect },
+            Rect { y: rect.y + pr2_oside, h: rect.h - pr2_oside, ..rect },
+        ]
+    }
+}
+
+/// Break the ties of `_ordered_pivot` towards the lowest `f_cost`: the orientation of
+/// a square `rect`, the pivots `f_pivot_tied` with the picked one, and the items next
+/// to the pivot giving it the same aspect ratio.
+///
+/// Each area of the split is costed as a group.
+///
+/// Returns the orientation, the pivot index and the items next to the pivot like
+/// `ordered_pivot_p1`.
+///
+/// __Complexity__: `O(ties⨯items.len())`
+fn ordered_pivot_break_ties<N, T, S, Q>(
+    rect: Rect<N>,
+    items: &[T],
+    (is_wide, p0_idx): (bool, usize),
+    f_item_size: &S,
+    f_pivot_tied: &Q,
+    f_cost: &dyn Fn(&[T], Rect<N>) -> N,
+) -> (bool, usize, (usize, N))
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    Q: Fn(&[T], usize, usize) -> bool,
+{
+    let orientations = if rect.w == rect.h { 2 } else { 1 };
+    let pivots = (0..items.len()).filter(|&idx| idx != p0_idx && f_pivot_tied(items, p0_idx, idx));
+    let pivots: Vec<usize> = std::iter::once(p0_idx).chain(pivots).collect();
+
+    let mut best = None;
+    for is_wide in [is_wide, !is_wide].iter().copied().take(orientations) {
+        let side = if is_wide { rect.h } else { rect.w };
+        for &p0_idx in &pivots {
+            let (l1, lrem) = items.split_at(p0_idx);
+            let (p, lrem) = lrem.split_first().unwrap();
+            let l1_size = if l1.is_empty() { None } else { Some(l1.iter().map(f_item_size).sum()) };
+            let p_size = f_item_size(p);
+            let (p1, (numer_b, denom_b)) = ordered_pivot_p1(side * side, p_size, lrem, f_item_size);
+
+            // The best split first, then the other ones with the same ratio
+            let mut t_size = p_size;
+            let p1_tied = lrem.iter().enumerate().filter_map(|(idx, item)| {
+                let size_item = f_item_size(item);
+                t_size += size_item;
+                let (numer, denom) = ratio(side * side, t_size, size_item);
+                let tied = idx != p1.0 && numer * denom_b == numer_b * denom;
+                if tied {
+                    Some((idx, t_size))
+                } else {
+                    None
+                }
+            });
+            for p1 in std::iter::once(p1).chain(p1_tied) {
+                let pl2_size = if lrem.is_empty() { None } else { Some(p1.1) };
+                let [r1, rp, r2, r3] =
+                    ordered_pivot_rects(rect, is_wide, l1_size, p_size, pl2_size);
+                let (l2, l3) = lrem.split_at((p1.0 + 1).min(lrem.len()));
+                let cost = f_cost(l1, r1)
+                    + f_cost(std::slice::from_ref(p), rp)
+                    + f_cost(l2, r2)
+                    + f_cost(l3, r3);
+                if best.is_none_or(|(cost_best, _)| cost < cost_best) {
+                    best = Some((cost, (is_wide, p0_idx, p1)));
+                }
+            }
+        }
+    }
+    best.unwrap().1
+}
+
+/// Distribute `items` inside `rect` by splitting it recursively around pivot by middle in 4 areas
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// __Complexity__: `O(2⨯items.len()⨯log_4(items.len()))`
+pub fn ordered_pivot_by_middle<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    ordered_pivot_by_middle_with_stats(rect, items, f_item_size, f_item_set_rect);
+}
+
+/// Distribute `items` inside `rect` like [`ordered_pivot_by_middle`], returning the
+/// recursion statistics.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// __Complexity__: `O(2⨯items.len()⨯log_4(items.len()))`
+pub fn ordered_pivot_by_middle_with_stats<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_set_rect: R,
+) -> LayoutStats
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    _ordered_pivot_by_middle_with_stats(rect, items, f_item_size, f_item_set_rect, None)
+}
+
+fn _ordered_pivot_by_middle_with_stats<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    mut f_item_set_rect: R,
+    f_tie_cost: TieCost<T, N>,
+) -> LayoutStats
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let mut stats = LayoutStats::default();
+    if !items.is_empty()
+        && !distribute_zero_area(rect, items, &mut f_item_set_rect)
+        && !distribute_single(rect, items, &f_item_size, &mut f_item_set_rect)
+    {
+        let scale = scale(rect, items, &f_item_size);
+        let f_item_size_scaled = |item: &T| f_item_size(item) * scale;
+        let f_pivot = |items: &[T]| items.len() / 2;
+        // An even number of items has 2 middle items
+        let f_pivot_tied =
+            |items: &[T], picked: usize, idx: usize| idx + 1 == picked && picked * 2 == items.len();
+        let f_pivot = (&f_pivot, &f_pivot_tied);
+        let (f_item_size, f_item_set_rect) = (&f_item_size_scaled, &mut f_item_set_rect);
+        stats.max_depth = _ordered_pivot(
+            rect,
+            items,
+            f_item_size,
+            f_item_set_rect,
+            f_pivot,
+            f_tie_cost,
+            &mut stats,
+        );
+    }
+    stats
+}
+
+/// Distribute `items` inside `rect` by splitting it recursively around pivot by size in 4 areas
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// __Complexity__: `O(items.len()^2)`
+pub fn ordered_pivot_by_size<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    ordered_pivot_by_size_with_stats(rect, items, f_item_size, f_item_set_rect);
+}
+
+/// Distribute `items` inside `rect` like [`ordered_pivot_by_size`], returning the
+/// recursion statistics.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// __Complexity__: `O(items.len()^2)`
+pub fn ordered_pivot_by_size_with_stats<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_set_rect: R,
+) -> LayoutStats
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    _ordered_pivot_by_size_with_stats(rect, items, f_item_size, f_item_set_rect, None)
+}
+
+fn _ordered_pivot_by_size_with_stats<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    mut f_item_set_rect: R,
+    f_tie_cost: TieCost<T, N>,
+) -> LayoutStats
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let mut stats = LayoutStats::default();
+    if !items.is_empty()
+        && !distribute_zero_area(rect, items, &mut f_item_set_rect)
+        && !distribute_single(rect, items, &f_item_size, &mut f_item_set_rect)
+    {
+        let scale = scale(rect, items, &f_item_size);
+        let f_item_size_scaled = |item: &T| f_item_size(item) * scale;
+        let f_pivot = |items: &[T]| {
+            items
+                .iter()
+                .enumerate()
+                .fold((0usize, N::zero()), |(idx_b, size_b), (idx, item)| {
+                    let size_item = f_item_size(item);
+                    if size_item > size_b {
+                        (idx, size_item)
+                    } else {
+                        (idx_b, size_b)
+                    }
+                })
+                .0
+        };
+        let f_pivot_tied = |items: &[T], picked: usize, idx: usize| {
+            f_item_size(&items[idx]) == f_item_size(&items[picked])
+        };
+        let f_pivot = (&f_pivot, &f_pivot_tied);
+        let (f_item_size, f_item_set_rect) = (&f_item_size_scaled, &mut f_item_set_rect);
+        stats.max_depth = _ordered_pivot(
+            rect,
+            items,
+            f_item_size,
+            f_item_set_rect,
+            f_pivot,
+            f_tie_cost,
+            &mut stats,
+        );
+    }
+    stats
+}
+
+/// Error returned by [`Rect::try_flip_h`] and [`Rect::try_flip_v`] when the rect
+/// doesn't fit within the container
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FlipError;
+
+impl fmt::Display for FlipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("rect doesn't fit within the container")
+    }
+}
+
+impl std::error::Error for FlipError {}
+
+/// Error returned by [`validate_coverage`] when the tiles don't cover the container area
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CoverageError<N> {
+    /// The container area
+    pub expected: N,
+    /// The sum of the tile areas
+    pub actual: N,
+    /// The absolute difference between `expected` and `actual`
+    pub discrepancy: N,
+}
+
+impl<N: fmt::Display> fmt::Display for CoverageError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tiles cover an area of {} instead of {} (discrepancy: {})",
+            self.actual, self.expected, self.discrepancy
+        )
+    }
+}
+
+impl<N: fmt::Debug + fmt::Display> std::error::Error for CoverageError<N> {}
+
+/// Check that the tiles of `items` cover exactly the `container` area.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// Returns the discrepancy between the container area and the sum of the tile areas
+/// if it exceeds `epsilon`.
+///
+/// __Complexity__: `O(items.len())`
+pub fn validate_coverage<N, T, F>(
+    container: Rect<N>,
+    items: &[T],
+    f_get_rect: F,
+    epsilon: N,
+) -> Result<(), CoverageError<N>>
+where
+    N: NumOps + PartialOrd + Zero + Copy,
+    F: Fn(&T) -> Rect<N>,
+{
+    let expected = container.w * container.h;
+    let actual = items.iter().map(f_get_rect).fold(N::zero(), |acc, r| acc + r.w * r.h);
+    let discrepancy = if expected > actual { expected - actual } else { actual - expected };
+    if discrepancy > epsilon {
+        Err(CoverageError { expected, actual, discrepancy })
+    } else {
+        Ok(())
+    }
+}
+
+/// Assert that the integer tiles of `items` tile exactly the `container`.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// Panics if a tile is outside of `container`, if two tiles overlap or if the tile
+/// areas don't sum to exactly the container area.
+///
+/// __Complexity__: `O(items.len()^2)`
+pub fn assert_exact_tiling<T, F>(container: Rect<i64>, items: &[T], f_get_rect: F)
+where
+    F: Fn(&T) -> Rect<i64>,
+{
+    let rects: Vec<Rect<i64>> = items.iter().map(f_get_rect).collect();
+    for (idx, r) in rects.iter().enumerate() {
+        assert!(container.contains_rect(r), "tile {} {:?} is outside of {:?}", idx, r, container);
+        for (other_idx, other) in rects.iter().enumerate().skip(idx + 1) {
+            assert!(
+                r.intersection(other).is_none(),
+                "tile {} {:?} overlaps tile {} {:?}",
+                idx,
+                r,
+                other_idx,
+                other
+            );
+        }
+    }
+    let area: i64 = rects.iter().map(|r| r.w * r.h).sum();
+    assert_eq!(area, container.w * container.h, "tiles don't cover the container area");
+}
+
+/// Snap the vertical edges of every tile with `f_snap_x` and the horizontal ones
+/// with `f_snap_y`.
+///
+/// Edges are snapped instead of origin and size, so tiles that were
+/// sharing an edge still do after snapping.
+fn snap_edges<T, F, G, P, Q>(
+    items: &mut [T],
+    f_get_rect: F,
+    mut f_set_rect: G,
+    f_snap_x: P,
+    f_snap_y: Q,
+) where
+    F: Fn(&T) -> Rect<f32>,
+    G: FnMut(&mut T, Rect<f32>),
+    P: Fn(f32) -> f32,
+    Q: Fn(f32) -> f32,
+{
+    for item in items.iter_mut() {
+        let r = f_get_rect(item);
+        let (x0, y0) = (f_snap_x(r.x), f_snap_y(r.y));
+        let (x1, y1) = (f_snap_x(r.x + r.w), f_snap_y(r.y + r.h));
+        f_set_rect(item, Rect { x: x0, y: y0, w: x1 - x0, h: y1 - y0 });
+    }
+}
+
+/// Snap the edges of every tile to the nearest half pixel.
+///
+/// Aligning edges on the pixel grid keeps 1px borders crisp.
+/// Tiles that were sharing an edge still do after snapping.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+/// - `f_set_rect` receive the snapped Rect of an item.
+///   Called once for each item and in a stable order.
+///
+/// __Complexity__: `O(items.len())`
+pub fn snap_half_pixel<T, F, G>(items: &mut [T], f_get_rect: F, f_set_rect: G)
+where
+    F: Fn(&T) -> Rect<f32>,
+    G: FnMut(&mut T, Rect<f32>),
+{
+    let f_snap = |v: f32| (v * 2.).round() / 2.;
+    snap_edges(items, f_get_rect, f_set_rect, f_snap, f_snap);
+}
+
+/// Snap the edges of every tile to the nearest multiple of `step`, within `container`.
+///
+/// Aligning edges on a coarse grid makes the layout look tidier.
+/// Tiles that were sharing an edge still do after snapping. The edges of `container`
+/// are snapping targets too and snapped edges are clamped to it, so tiles stay
+/// inside it and keep its edges even if they aren't multiples of `step`.
+/// Tiles smaller than `step` can collapse to an empty size.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+/// - `f_set_rect` receive the snapped Rect of an item.
+///   Called once for each item and in a stable order.
+///
+/// __Complexity__: `O(items.len())`
+pub fn snap_to_grid<T, F, G>(
+    container: Rect<f32>,
+    items: &mut [T],
+    f_get_rect: F,
+    f_set_rect: G,
+    step: f32,
+) where
+    F: Fn(&T) -> Rect<f32>,
+    G: FnMut(&mut T, Rect<f32>),
+{
+    let f_snap = |v: f32, min: f32, max: f32| {
+        let snapped = ((v / step).round() * step).max(min).min(max);
+        if v - min < (v - snapped).abs() {
+            min
+        } else if max - v < (v - snapped).abs() {
+            max
+        } else {
+            snapped
+        }
+    };
+    let (x1, y1) = (container.x + container.w, container.y + container.h);
+    let f_snap_x = |v| f_snap(v, container.x, x1);
+    let f_snap_y = |v| f_snap(v, container.y, y1);
+    snap_edges(items, f_get_rect, f_set_rect, f_snap_x, f_snap_y);
+}
+
+/// Distribute the entries of `map` inside `rect` with [`squarify`].
+///
+/// Entries are sorted by size in descending order, then by key, so the output
+/// doesn't depend on the map iteration order.
+///
+/// __Complexity__: `O(map.len()⨯log_2(map.len()))`
+pub fn squarify_map<N, K, H>(rect: Rect<N>, map: HashMap<K, N, H>) -> Vec<(K, Rect<N>)>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    K: Ord,
+{
+    let mut items: Vec<(K, N, Rect<N>)> =
+        map.into_iter().map(|(k, n)| (k, n, Rect::from_size(N::zero(), N::zero()))).collect();
+    items.sort_by(|(ka, na, _), (kb, nb, _)| {
+        nb.partial_cmp(na).unwrap_or(Ordering::Equal).then_with(|| ka.cmp(kb))
+    });
+    squarify(rect, &mut items[..], |&(_, n, _)| n, |(_, _, item_r), r| *item_r = r);
+    items.into_iter().map(|(k, _, r)| (k, r)).collect()
+}
+
+/// Distribute the entries of `map` inside `rect` with [`squarify`], in key order.
+///
+/// Unlike [`squarify_map`], entries are not sorted by size, so tiles follow the
+/// meaningful order of keys, like time buckets.
+///
+/// __Complexity__: `O(3⨯map.len())`
+pub fn squarify_btree<N, K>(rect: Rect<N>, map: BTreeMap<K, N>) -> Vec<(K, Rect<N>)>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+{
+    let mut items: Vec<(K, N, Rect<N>)> =
+        map.into_iter().map(|(k, n)| (k, n, Rect::from_size(N::zero(), N::zero()))).collect();
+    squarify(rect, &mut items[..], |&(_, n, _)| n, |(_, _, item_r), r| *item_r = r);
+    items.into_iter().map(|(k, _, r)| (k, r)).collect()
+}
+
+/// Compute the fraction of the tiles visible in `container` whose visible area is
+/// below `min_readable_area`.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// A value close to 1 means most tiles are too small to be readable. Tiles are
+/// clipped to `container`, like a zoomed-in view, and tiles outside of it are
+/// ignored. Returns 0 if no tile is visible.
+///
+/// __Complexity__: `O(items.len())`
+pub fn density<N, T, F>(container: Rect<N>, items: &[T], f_get_rect: F, min_readable_area: N) -> N
+where
+    N: Float,
+    F: Fn(&T) -> Rect<N>,
+{
+    let (mut visible, mut unreadable) = (0, 0);
+    for r in items.iter().filter_map(|item| f_get_rect(item).intersection(&container)) {
+        visible += 1;
+        if r.w * r.h < min_readable_area {
+            unreadable += 1;
+        }
+    }
+    if visible == 0 {
+        return N::zero();
+    }
+    N::from(unreadable).unwrap() / N::from(visible).unwrap()
+}
+
+/// Count the tiles that aren't readable, see [`Rect::is_readable`].
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// Unlike [`density`], thin slivers are unreadable whatever their area, so a UI
+/// can fall back to showing the top items and an "other" tile.
+///
+/// __Complexity__: `O(items.len())`
+pub fn unreadable_count<N, T, F>(items: &[T], f_get_rect: F, min_w: N, min_h: N) -> usize
+where
+    N: NumOps + PartialOrd + Copy,
+    F: Fn(&T) -> Rect<N>,
+{
+    items.iter().map(f_get_rect).filter(|r| !r.is_readable(min_w, min_h)).count()
+}
+
+/// Distribute `items` inside `rect` in rows of `row_height` from top to bottom,
+/// filling each row from left to right.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// An item of size `s` is `s / row_height` wide and a new row is started when the
+/// next item doesn't fit. Each row is then scaled to span the whole `rect` width,
+/// including the last one and a row holding a single item wider than `rect`. Rows are
+/// not scaled vertically, so they can overflow `rect` height.
+///
+/// If `row_height` is zero, items get zero-area rects at the origin of `rect`.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn brick<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    row_height: N,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let mut row_rect = Rect { h: row_height, ..rect };
+    if distribute_zero_area(row_rect, items, &mut f_item_set_rect) {
+        return;
+    }
+    let mut items = items;
+    while !items.is_empty() {
+        let mut row_w = N::zero();
+        let row_len = items
+            .iter()
+            .position(|item| {
+                row_w += f_item_size(item) / row_height;
+                row_w > rect.w
+            })
+            .unwrap_or(items.len())
+            .max(1);
+        let (row, tail) = items.split_at_mut(row_len);
+        items = tail;
+        dice(row_rect, row, &f_item_size, &mut f_item_set_rect);
+        row_rect.y += row_height;
+    }
+}
+
+/// Compute the prefix sums of the item sizes, starting with zero.
+///
+/// __Complexity__: `O(items.len())`
+fn prefix_sums<N, T, S>(items: &[T], f_item_size: S) -> Vec<N>
+where
+    N: NumAssignOps + Zero + Copy,
+    S: Fn(&T) -> N,
+{
+    let mut size_total = N::zero();
+    let mut sums = Vec::with_capacity(items.len() + 1);
+    sums.push(size_total);
+    for item in items {
+        size_total += f_item_size(item);
+        sums.push(size_total);
+    }
+    sums
+}
+
+/// Find the index of the prefix sum closest to `target`, starting from index `from`.
+///
+/// __Complexity__: `O(sums.len())`
+fn closest_prefix_sum<N>(sums: &[N], from: usize, target: N) -> usize
+where
+    N: NumOps + PartialOrd + Copy,
+{
+    let mut idx =
+        sums[from..].iter().position(|&s| s >= target).map_or(sums.len() - 1, |idx| from + idx);
+    if idx > from && target - sums[idx - 1] < sums[idx] - target {
+        idx -= 1;
+    }
+    idx
+}
+
+/// Split `rect` along its longest side in `k` regions holding roughly the same total
+/// item size.
+///
+/// - `f_item_size` provide the size of an item
+///
+/// Returns each region with the range of `items` it holds. Regions are in the
+/// same order as `items` and cover `rect`, their extent is proportional to their
+/// total item size. Some ranges are empty if `k > items.len()`.
+///
+/// __Complexity__: `O(items.len() + k)`
+pub fn split_k<N, T, S>(
+    rect: Rect<N>,
+    items: &[T],
+    k: usize,
+    f_item_size: S,
+) -> Vec<(Rect<N>, Range<usize>)>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + NumCast,
+    S: Fn(&T) -> N,
+{
+    let sums = prefix_sums(items, f_item_size);
+    let size_total = sums[items.len()];
+
+    let is_wide = rect.w > rect.h;
+    let (start, end) = if is_wide { (rect.x, rect.x + rect.w) } else { (rect.y, rect.y + rect.h) };
+    let position = |sum: N| {
+        if size_total.is_zero() {
+            start
+        } else {
+            (start * (size_total - sum) + end * sum) / size_total
+        }
+    };
+    let region = |p0: N, p1: N| {
+        if is_wide {
+            Rect { x: p0, w: p1 - p0, ..rect }
+        } else {
+            Rect { y: p0, h: p1 - p0, ..rect }
+        }
+    };
+
+    let mut regions = Vec::with_capacity(k);
+    let (mut idx0, mut p0) = (0, start);
+    for j in 1..k {
+        let target = size_total * N::from(j).unwrap() / N::from(k).unwrap();
+        let idx1 = closest_prefix_sum(&sums, idx0, target);
+        let p1 = position(sums[idx1]);
+        regions.push((region(p0, p1), idx0..idx1));
+        idx0 = idx1;
+        p0 = p1;
+    }
+    if k > 0 {
+        regions.push((region(p0, end), idx0..items.len()));
+    }
+    regions
+}
+
+/// Distribute `items` inside `rect` like [`squarify`], but reusing the number of items
+/// per strip of a previous layout.
+///
+/// - `strips` is the number of items per strip returned by a previous call,
+///   missing strips are computed by the squarified algorithm.
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Returns the number of items per strip of this layout.
+///
+/// When data change a little between frames, reusing the strip structure trades some
+/// aspect ratio quality for tiles that don't jump around. Pass an empty `strips` to
+/// start from a fresh layout when data change a lot.
+///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_reusing_strips<N, T, S, R>(
+    mut rect: Rect<N>,
+    mut items: &mut [T],
+    strips: &[usize],
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) -> Vec<usize>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    if distribute_zero_area(rect, items, &mut f_item_set_rect) {
+        return Vec::new();
+    }
+    if distribute_single(rect, items, &f_item_size, &mut f_item_set_rect) {
+        return vec![1];
+    }
+    let scale = scale(rect, items, &f_item_size);
+    let f_item_size = |item: &T| f_item_size(item) * scale;
+    let mut strips = strips.iter().copied();
+    let mut layout_strips = Vec::new();
+    while !items.is_empty() {
+        let is_wide = rect.w > rect.h;
+        let side = if is_wide { rect.h } else { rect.w };
+        let strip = match strips.next() {
+            Some(n) if n > 0 => {
+                let n = n.min(items.len());
+                (n, items[..n].iter().map(f_item_size).sum())
+            }
+            _ => squarify_strip(side, items, &f_item_size),
+        };
+        layout_strips.push(strip.0);
+        items = squarify_place_strip(
+            &mut rect,
+            is_wide,
+            items,
+            strip,
+            &SquarifyOptions::new(),
+            &f_item_size,
+            &mut f_item_set_rect,
+        );
+    }
+    layout_strips
+}
+
+/// Write `text` to `f` with HTML special characters escaped
+fn write_html_escaped(f: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => f.push_str("&amp;"),
+            '<' => f.push_str("&lt;"),
+            '>' => f.push_str("&gt;"),
+            '"' => f.push_str("&quot;"),
+            c => f.push(c),
+        }
+    }
+}
+
+/// Export the tiles of `items` as an HTML snapshot.
+///
+/// - `f_item_size` provide the size of an item, exported as `data-size`
+/// - `f_get_rect` provide the distributed Rect of an item
+/// - `f_label` provide the label of an item
+///
+/// The container is a `<div>` with one absolutely positioned `<div>` per item,
+/// positions and sizes are relative to `container` and in its units.
+///
+/// __Complexity__: `O(items.len())`
+pub fn to_html<N, T, S, F, L, D>(
+    container: Rect<N>,
+    items: &[T],
+    f_item_size: S,
+    f_get_rect: F,
+    f_label: L,
+) -> String
+where
+    N: NumOps + Copy + fmt::Display,
+    S: Fn(&T) -> N,
+    F: Fn(&T) -> Rect<N>,
+    L: Fn(&T) -> D,
+    D: fmt::Display,
+{
+    use std::fmt::Write;
+
+    let mut f = String::new();
+    writeln!(
+        &mut f,
+        r#"<div style="position: relative; width: {}px; height: {}px;">"#,
+        container.w, container.h
+    )
+    .unwrap();
+    for item in items {
+        let r = f_get_rect(item);
+        write!(
+            &mut f,
+            r#"  <div style="position: absolute; left: {}px; top: {}px; width: {}px; height: {}px;" data-size="{}">"#,
+            r.x - container.x,
+            r.y - container.y,
+            r.w,
+            r.h,
+            f_item_size(item)
+        )
+        .unwrap();
+        write_html_escaped(&mut f, &f_label(item).to_string());
+        writeln!(&mut f, "</div>").unwrap();
+    }
+    writeln!(&mut f, "</div>").unwrap();
+
+    f
+}
+
+/// Distribute `items` across `containers` with [`squarify`].
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item container index and distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Items are split in consecutive ranges, each container receiving a total item size
+/// as close as possible to its share of the total containers area. If the containers
+/// have no area, every item gets a zero-area rect in the first container.
+///
+/// Panics if `containers` is empty.
+///
+/// __Complexity__: `O(4⨯items.len() + containers.len())`
+pub fn squarify_multi<N, T, S, R>(
+    containers: &[Rect<N>],
+    items: &mut [T],
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, usize, Rect<N>),
+{
+    assert!(!containers.is_empty(), "containers must not be empty");
+    let area_total: N = containers.iter().map(|r| r.w * r.h).sum();
+    if area_total.is_zero() {
+        let mut f_item_set_rect = |item: &mut T, r| f_item_set_rect(item, 0, r);
+        distribute_zero_area(containers[0], items, &mut f_item_set_rect);
+        return;
+    }
+    let sums = prefix_sums(items, &f_item_size);
+    let size_total = sums[items.len()];
+    let mut area = N::zero();
+    let mut idx0 = 0;
+    for (container_idx, &container) in containers.iter().enumerate() {
+        area += container.w * container.h;
+        let idx1 = if container_idx + 1 == containers.len() {
+            items.len()
+        } else {
+            closest_prefix_sum(&sums, idx0, size_total * area / area_total)
+        };
+        squarify(container, &mut items[idx0..idx1], &f_item_size, |item, r| {
+            f_item_set_rect(item, container_idx, r)
+        });
+        idx0 = idx1;
+    }
+}
+
+/// Distribute `items` inside `rect`, giving `focus_fraction` of the area to the
+/// `items[focus_index]` tile in the top left corner and squarifying the other items
+/// in the remaining area.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// The focused tile spans the short side of `rect`, `focus_fraction` is expected
+/// to be between 0 and 1.
+///
+/// Panics if `focus_index` is out of bounds.
+///
+/// __Complexity__: `O(5⨯items.len())`
+pub fn focus<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    focus_index: usize,
+    focus_fraction: N,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    assert!(focus_index < items.len(), "focus_index out of bounds");
+    let (rect_focus, rect_rest) = if rect.w > rect.h {
+        let w = rect.w * focus_fraction;
+        (Rect { w, ..rect }, Rect { x: rect.x + w, w: rect.w - w, ..rect })
+    } else {
+        let h = rect.h * focus_fraction;
+        (Rect { h, ..rect }, Rect { y: rect.y + h, h: rect.h - h, ..rect })
+    };
+
+    let rect_zero = Rect::from_size(N::zero(), N::zero());
+    let mut rest: Vec<(usize, Rect<N>)> =
+        (0..items.len()).filter(|&idx| idx != focus_index).map(|idx| (idx, rect_zero)).collect();
+    squarify(
+        rect_rest,
+        &mut rest[..],
+        |&(idx, _)| f_item_size(&items[idx]),
+        |(_, item_r), r| *item_r = r,
+    );
+
+    let mut rest = rest.into_iter();
+    for (idx, item) in items.iter_mut().enumerate() {
+        if idx == focus_index {
+            f_item_set_rect(item, rect_focus);
+        } else if let Some((_, r)) = rest.next() {
+            f_item_set_rect(item, r);
+        }
+    }
+}
+
+fn _squarify_tree<N, T, S, C, A, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    path: &mut Vec<usize>,
+    f_item_size: &S,
+    f_children: &C,
+    f_level_axis: &A,
+    f_item_set_rect: &mut R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    C: Fn(&mut T) -> &mut [T],
+    A: Fn(usize) -> Option<Axis>,
+    R: FnMut(&mut T, &[usize], Rect<N>),
+{
+    let mut rects = Vec::with_capacity(items.len());
+    match f_level_axis(path.len()) {
+        Some(axis) => fill(rect, items, axis, f_item_size, |_, r| rects.push(r)),
+        None => squarify(rect, items, f_item_size, |_, r| rects.push(r)),
+    }
+    for (idx, (item, r)) in items.iter_mut().zip(rects).enumerate() {
+        path.push(idx);
+        f_item_set_rect(item, path, r);
+        let children = f_children(item);
+        if !children.is_empty() {
+            _squarify_tree(
+                r,
+                children,
+                path,
+                f_item_size,
+                f_children,
+                f_level_axis,
+                f_item_set_rect,
+            );
+        }
+        path.pop();
+    }
+}
+
+/// Distribute a tree of `items` inside `rect` with [`squarify`], the children of a node
+/// being distributed inside the node tile.
+///
+/// - `f_item_size` provide the size of a node, children included
+/// - `f_children` provide the children of a node
+/// - `f_item_set_rect` receive the node path and distributed Rect.
+///   The path is the index of the node among its siblings at each depth from the root.
+///   Called once for each node, parents before their children, and in a stable order.
+///
+/// __Complexity__: `O(4⨯nodes)`
+pub fn squarify_tree<N, T, S, C, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_children: C,
+    f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    C: Fn(&mut T) -> &mut [T],
+    R: FnMut(&mut T, &[usize], Rect<N>),
+{
+    squarify_tree_with_axis(rect, items, f_item_size, f_children, |_| None, f_item_set_rect);
+}
+
+/// Distribute a tree of `items` inside `rect` like [`squarify_tree`], but with
+/// the orientation of some levels forced.
+///
+/// - `f_item_size` provide the size of a node, children included
+/// - `f_children` provide the children of a node
+/// - `f_level_axis` provide the axis used to [`fill`] the nodes at a depth, the
+///   root nodes being at depth 0. Nodes are squarified at depths returning `None`.
+/// - `f_item_set_rect` receive the node path and distributed Rect.
+///   The path is the index of the node among its siblings at each depth from the root.
+///   Called once for each node, parents before their children, and in a stable order.
+///
+/// __Complexity__: `O(4⨯nodes)`
+pub fn squarify_tree_with_axis<N, T, S, C, A, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_children: C,
+    f_level_axis: A,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    C: Fn(&mut T) -> &mut [T],
+    A: Fn(usize) -> Option<Axis>,
+    R: FnMut(&mut T, &[usize], Rect<N>),
+{
+    let mut path = Vec::new();
+    _squarify_tree(
+        rect,
+        items,
+        &mut path,
+        &f_item_size,
+        &f_children,
+        &f_level_axis,
+        &mut f_item_set_rect,
+    );
+}
+
+/// Sort `items` by size in descending order, which maximizes the output quality of
+/// most algorithms.
+///
+/// - `f_item_size` provide the size of an item
+///
+/// The sort is stable.
+///
+/// __Complexity__: `O(items.len()⨯log_2(items.len()))`
+pub fn sort_desc_by_size<N, T, S>(items: &mut [T], f_item_size: S)
+where
+    N: PartialOrd,
+    S: Fn(&T) -> N,
+{
+    items.sort_by(|a, b| f_item_size(b).partial_cmp(&f_item_size(a)).unwrap_or(Ordering::Equal));
+}
+
+/// Check if `items` are sorted by size in descending order.
+///
+/// - `f_item_size` provide the size of an item
+///
+/// __Complexity__: `O(items.len())`
+pub fn is_sorted_desc_by_size<N, T, S>(items: &[T], f_item_size: S) -> bool
+where
+    N: PartialOrd,
+    S: Fn(&T) -> N,
+{
+    items.windows(2).all(|w| f_item_size(&w[0]) >= f_item_size(&w[1]))
+}
+
+/// Distribute `items` already sorted by size in descending order inside `rect`
+/// with [`squarify`].
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Use it to lay out data sorted once with [`sort_desc_by_size`] every frame.
+/// Items are checked to be sorted in debug builds only, unsorted items are safe
+/// but the output quality is worse.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_sorted<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    debug_assert!(
+        is_sorted_desc_by_size(items, &f_item_size),
+        "items must be sorted by size in descending order"
+    );
+    squarify(rect, items, f_item_size, f_item_set_rect);
+}
+
+/// Sort `items` with `f_compare`, then distribute them inside `rect` with [`squarify`].
+///
+/// - `f_compare` compare two items, for example by bucketed size then by category
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in the sorted order.
+///
+/// The order affects both which items share a strip and the callback order, so
+/// items that compare close end up close in the layout. The sort is stable.
+///
+/// __Complexity__: `O(items.len()⨯log_2(items.len()) + 3⨯items.len())`
+pub fn squarify_sorted_by<N, T, C, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_compare: C,
+    f_item_size: S,
+    f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    C: Fn(&T, &T) -> Ordering,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    items.sort_by(f_compare);
+    squarify(rect, items, f_item_size, f_item_set_rect);
+}
+
+/// Compute a bin index in `0..bins` for each item using logarithmic bucketing of the
+/// item sizes, to map items to a color ramp for example.
+///
+/// - `f_item_size` provide the size of an item
+///
+/// The smallest positive size goes to the first bin and the largest to the last one.
+/// Non positive sizes go to the first bin.
+///
+/// Panics if `bins` is zero.
+///
+/// __Complexity__: `O(2⨯items.len())`
+pub fn bin_by_size<N, T, S>(items: &[T], f_item_size: S, bins: usize) -> Vec<usize>
+where
+    N: Float,
+    S: Fn(&T) -> N,
+{
+    assert!(bins > 0, "bins must not be zero");
+    let (min, max) = items
+        .iter()
+        .map(&f_item_size)
+        .filter(|&size| size > N::zero())
+        .fold((N::infinity(), N::zero()), |(min, max), size| (min.min(size), max.max(size)));
+    let (ln_min, ln_range) = (min.ln(), max.ln() - min.ln());
+    let bins_n = N::from(bins).unwrap();
+    items
+        .iter()
+        .map(|item| {
+            let size = f_item_size(item);
+            if size <= N::zero() || ln_range <= N::zero() {
+                0
+            } else {
+                let t = (size.ln() - ln_min) / ln_range;
+                (t * bins_n).floor().to_usize().unwrap_or(0).min(bins - 1)
+            }
+        })
+        .collect()
+}
+
+/// Compute the average aspect ratio of the tiles, 1 being all squares.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// Returns 0 if there is no items.
+///
+/// __Complexity__: `O(items.len())`
+pub fn average_aspect_ratio<N, T, F>(items: &[T], f_get_rect: F) -> N
+where
+    N: Float,
+    F: Fn(&T) -> Rect<N>,
+{
+    if items.is_empty() {
+        return N::zero();
+    }
+    let sum =
+        items.iter().map(|item| f_get_rect(item).aspect_ratio()).fold(N::zero(), |a, b| a + b);
+    sum / N::from(items.len()).unwrap()
+}
+
+/// Compute the average displacement of tiles between two layouts of the same items,
+/// 0 meaning no tile moved.
+///
+/// The displacement of a tile is the euclidean distance between its `(x, y, w, h)`
+/// in `previous` and in `current`. Extra tiles of the longest layout are ignored.
+///
+/// Returns 0 if there is no tiles.
+///
+/// __Complexity__: `O(min(previous.len(), current.len()))`
+pub fn stability<N>(previous: &[Rect<N>], current: &[Rect<N>]) -> N
+where
+    N: Float,
+{
+    let len = previous.len().min(current.len());
+    if len == 0 {
+        return N::zero();
+    }
+    let sum = previous.iter().zip(current).fold(N::zero(), |sum, (a, b)| sum + displacement(a, b));
+    sum / N::from(len).unwrap()
+}
+
+/// Euclidean distance between `(x, y, w, h)` of `a` and `b`
+fn displacement<N: Float>(a: &Rect<N>, b: &Rect<N>) -> N {
+    let (dx, dy, dw, dh) = (a.x - b.x, a.y - b.y, a.w - b.w, a.h - b.h);
+    (dx * dx + dy * dy + dw * dw + dh * dh).sqrt()
+}
+
+/// Distribute `items` inside `rect` like [`squarify`], but preferring strips that keep
+/// tiles close to their `previous` layout.
+///
+/// - `previous` is the previous layout, `previous[i]` being the tile of `items[i]`.
+///   Items without a previous tile don't constrain the layout.
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// For each strip, the squarified strip and the strips with one item less or one item
+/// more are evaluated, and the strip with the lowest displacement of its tiles is kept.
+///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// __Complexity__: `O(11⨯items.len())`
+pub fn squarify_stable<N, T, S, R>(
+    mut rect: Rect<N>,
+    mut items: &mut [T],
+    previous: &[Rect<N>],
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: Float + NumAssignOps + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    if distribute_zero_area(rect, items, &mut f_item_set_rect)
+        || distribute_single(rect, items, &f_item_size, &mut f_item_set_rect)
+    {
+        return;
+    }
+    let scale = scale(rect, items, &f_item_size);
+    let f_item_size = |item: &T| f_item_size(item) * scale;
+    let options = SquarifyOptions::new();
+    let mut offset = 0;
+    let mut strip_rects = Vec::new();
+    while !items.is_empty() {
+        let is_wide = rect.w > rect.h;
+        let side = if is_wide { rect.h } else { rect.w };
+        let greedy = squarify_strip(side, items, &f_item_size);
+        let mut strip_cost = |strip: (usize, N), items: &mut [T]| {
+            strip_rects.clear();
+            let mut rect = rect;
+            squarify_place_strip(
+                &mut rect,
+                is_wide,
+                items,
+                strip,
+                &options,
+                &f_item_size,
+                &mut |_, r| strip_rects.push(r),
+            );
+            let previous = previous.get(offset..).unwrap_or(&[]);
+            previous
+                .iter()
+                .zip(&strip_rects)
+                .fold(N::zero(), |sum, (a, b)| sum + displacement(a, b))
+        };
+        let mut strip = greedy;
+        let mut cost = strip_cost(greedy, items);
+        for split_idx in [greedy.0.saturating_sub(1), greedy.0 + 1] {
+            if split_idx > 0 && split_idx <= items.len() {
+                let candidate = (split_idx, items[..split_idx].iter().map(f_item_size).sum());
+                let candidate_cost = strip_cost(candidate, items);
+                if candidate_cost < cost {
+                    strip = candidate;
+                    cost = candidate_cost;
+                }
+            }
+        }
+        offset += strip.0;
+        items = squarify_place_strip(
+            &mut rect,
+            is_wide,
+            items,
+            strip,
+            &options,
+            &f_item_size,
+            &mut f_item_set_rect,
+        );
+    }
+}
+
+/// Compute the strips [`squarify`] would distribute `items` in, without placing them.
+///
+/// - `f_item_size` provide the size of an item
+///
+/// Returns the end index of each strip, the last one being `items.len()`. A single
+/// item or a `rect` without area gives a single strip.
+///
+/// __Complexity__: `O(2⨯items.len())`
+pub fn squarify_splits<N, T, S>(mut rect: Rect<N>, mut items: &[T], f_item_size: S) -> Vec<usize>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+{
+    if items.len() == 1 || (!items.is_empty() && (rect.w * rect.h).is_zero()) {
+        return vec![items.len()];
+    }
+    let scale = scale(rect, items, &f_item_size);
+    let f_item_size = |item: &T| f_item_size(item) * scale;
+    let mut splits = Vec::new();
+    let mut split_idx = 0;
+    while !items.is_empty() {
+        let is_wide = rect.w > rect.h;
+        let side = if is_wide { rect.h } else { rect.w };
+        let (len, size_strip) = squarify_strip(side, items, &f_item_size);
+        squarify_split_strip(&mut rect, is_wide, size_strip, len == items.len());
+        items = &items[len..];
+        split_idx += len;
+        splits.push(split_idx);
+    }
+    splits
+}
+
+/// Distribute `items` inside `rect` minus `(top, right, bottom, left)` margins
+/// with [`squarify`], to keep room for a title or a legend.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_with_margins<N, T, S, R>(
+    rect: Rect<N>,
+    (top, right, bottom, left): (N, N, N, N),
+    items: &mut [T],
+    f_item_size: S,
+    f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    squarify(rect.inset(top, right, bottom, left), items, f_item_size, f_item_set_rect);
+}
+
+/// Distribute `items` inside `rect` with [`squarify`] using a blend of their size and
+/// their importance, `size^(1 - blend) ⨯ importance^blend`.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_importance` provide the importance of an item
+/// - `blend` is between 0 (size only) and 1 (importance only)
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_weighted<N, T, S, W, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_importance: W,
+    blend: N,
+    f_item_set_rect: R,
+) where
+    N: Float + NumAssignOps + Sum,
+    S: Fn(&T) -> N,
+    W: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let size_exp = N::one() - blend;
+    squarify(
+        rect,
+        items,
+        |item| f_item_size(item).powf(size_exp) * f_item_importance(item).powf(blend),
+        f_item_set_rect,
+    );
+}
+
+/// Distribute `items` inside `rect` like [`binary`], without any heap allocation.
+///
+/// - `CAP` is the maximum number of items
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Panics if `items.len() > CAP`.
+///
+/// __Complexity__: `O(3⨯items.len()⨯log_2(items.len()))`
+pub fn binary_const<N, T, S, R, const CAP: usize>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    assert!(items.len() <= CAP, "binary_const capacity exceeded");
+    if !items.is_empty() {
+        let mut size_total = N::zero();
+        let mut sums = [N::zero(); CAP];
+        for (sum, item) in sums.iter_mut().zip(items.iter()) {
+            size_total += f_item_size(item);
+            *sum = size_total;
+        }
+        let sums = &sums[..items.len()];
+        let stats = &mut LayoutStats::default();
+        _binary(rect, items, &mut f_item_set_rect, sums, (N::zero(), size_total), None, stats);
+    }
+}
+
+/// Merge runs of consecutive tiles whose item size is below `min_size` and that
+/// share a whole edge, so they can be displayed as a single labeled tile.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_get_rect` provide the distributed Rect of an item
+/// - `f_merged` receive the range of merged items and their merged Rect.
+///   Called once for each run of at least 2 merged tiles and in a stable order.
+///
+/// Sizes are compared instead of tile areas, so padded or clamped tiles are merged
+/// like the items they show. A tile extends the run if it starts where the previous
+/// tile ends and spans the same rows or columns as the run, edges closer than
+/// `epsilon` being considered equal. The merged Rect area is the sum of the merged
+/// tiles areas.
+///
+/// __Complexity__: `O(items.len())`
+pub fn merge_adjacent_below<N, T, S, F, G>(
+    items: &[T],
+    f_item_size: S,
+    min_size: N,
+    f_get_rect: F,
+    epsilon: N,
+    mut f_merged: G,
+) where
+    N: NumOps + PartialOrd + Copy,
+    S: Fn(&T) -> N,
+    F: Fn(&T) -> Rect<N>,
+    G: FnMut(Range<usize>, Rect<N>),
+{
+    let close = |a: N, b: N| if a > b { a - b <= epsilon } else { b - a <= epsilon };
+    let extends = |merged: &Rect<N>, prev: &Rect<N>, r: &Rect<N>| {
+        let side_by_side = close(prev.max_x(), r.x) && close(merged.y, r.y) && close(merged.h, r.h);
+        let stacked = close(prev.max_y(), r.y) && close(merged.x, r.x) && close(merged.w, r.w);
+        side_by_side || stacked
+    };
+    // (start, merged, previous tile)
+    let mut run: Option<(usize, Rect<N>, Rect<N>)> = None;
+    let mut flush = |run: Option<(usize, Rect<N>, Rect<N>)>, end: usize| {
+        if let Some((start, r, _)) = run {
+            if end - start > 1 {
+                f_merged(start..end, r);
+            }
+        }
+    };
+    for (idx, item) in items.iter().enumerate() {
+        if f_item_size(item) >= min_size {
+            flush(run.take(), idx);
+            continue;
+        }
+        let r = f_get_rect(item);
+        match run {
+            Some((start, merged, prev)) if extends(&merged, &prev, &r) => {
+                run = Some((start, merged.union(&r), r));
+            }
+            _ => {
+                flush(run.take(), idx);
+                run = Some((idx, r, r));
+            }
+        }
+    }
+    flush(run, items.len());
+}
+
+/// Compute the outer boundary of the union of the tile rects, as a rectilinear
+/// polygon.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// Vertices are listed clockwise (with `y` going down), starting from the top
+/// left most vertex, without repeating the first vertex and without collinear
+/// vertices. If the union is made of disconnected parts, only the boundary of
+/// the part containing the top left most vertex is returned. Holes are ignored.
+///
+/// __Complexity__: `O(items.len()^3)`
+pub fn outline<N, T, F>(items: &[T], f_get_rect: F) -> Vec<(N, N)>
+where
+    N: NumOps + PartialOrd + Zero + Copy,
+    F: Fn(&T) -> Rect<N>,
+{
+    let rects: Vec<Rect<N>> =
+        items.iter().map(&f_get_rect).filter(|r| r.w > N::zero() && r.h > N::zero()).collect();
+    let coords = |f: &dyn Fn(&Rect<N>) -> (N, N)| {
+        let mut coords: Vec<N> = rects
+            .iter()
+            .flat_map(|r| {
+                let (a, b) = f(r);
+                [a, a + b]
+            })
+            .collect();
+        coords.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        coords.dedup_by(|a, b| a == b);
+        coords
+    };
+    let xs = coords(&|r| (r.x, r.w));
+    let ys = coords(&|r| (r.y, r.h));
+    if rects.is_empty() {
+        return Vec::new();
+    }
+
+    // Cover a grid of cells made of every rect edge
+    let (nx, ny) = (xs.len() - 1, ys.len() - 1);
+    let covered = |i: usize, j: usize| {
+        i < nx
+            && j < ny
+            && rects.iter().any(|r| {
+                r.x <= xs[i] && xs[i + 1] <= r.x + r.w && r.y <= ys[j] && ys[j + 1] <= r.y + r.h
+            })
+    };
+    let cells: Vec<bool> =
+        (0..ny).flat_map(|j| (0..nx).map(move |i| (i, j))).map(|(i, j)| covered(i, j)).collect();
+    let is_covered = |i: usize, j: usize| i < nx && j < ny && cells[j * nx + i];
+
+    // Collect clockwise boundary edges between grid points `i + j * (nx + 1)`
+    let point = |i: usize, j: usize| i + j * (nx + 1);
+    let mut edges: Vec<Vec<usize>> = vec![Vec::new(); (nx + 1) * (ny + 1)];
+    for j in 0..ny {
+        for i in 0..nx {
+            if !is_covered(i, j) {
+                continue;
+            }
+            if j == 0 || !is_covered(i, j - 1) {
+                edges[point(i, j)].push(point(i + 1, j));
+            }
+            if !is_covered(i + 1, j) {
+                edges[point(i + 1, j)].push(point(i + 1, j + 1));
+            }
+            if !is_covered(i, j + 1) {
+                edges[point(i + 1, j + 1)].push(point(i, j + 1));
+            }
+            if i == 0 || !is_covered(i - 1, j) {
+                edges[point(i, j + 1)].push(point(i, j));
+            }
+        }
+    }
+
+    // Walk the boundary from the top left most point, skipping collinear points
+    let start = match edges.iter().position(|e| !e.is_empty()) {
+        Some(start) => start,
+        None => return Vec::new(),
+    };
+    let coord = |p: usize| (p % (nx + 1), p / (nx + 1));
+    let mut path = vec![start];
+    let mut current = start;
+    while let Some(next) = edges[current].pop() {
+        if next == start {
+            break;
+        }
+        path.push(next);
+        current = next;
+    }
+    let n = path.len();
+    (0..n)
+        .filter(|&k| {
+            let (pi, pj) = coord(path[(k + n - 1) % n]);
+            let (i, j) = coord(path[k]);
+            let (ni, nj) = coord(path[(k + 1) % n]);
+            !((pi == i && i == ni) || (pj == j && j == nj))
+        })
+        .map(|k| {
+            let (i, j) = coord(path[k]);
+            (xs[i], ys[j])
+        })
+        .collect()
+}
+
+/// Distribute `items` inside `rect` with [`squarify`] in a canonical order, so
+/// the same set of items always produces the same layout.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_key` provide the key of an item, used to order items of the same size
+///
+/// Items are sorted by size in descending order, then by key. Returns the index
+/// of each item in `items` with its distributed Rect, in the canonical order.
+///
+/// __Complexity__: `O(items.len()⨯log_2(items.len()))`
+pub fn squarify_canonical<N, T, K, S, F>(
+    rect: Rect<N>,
+    items: &[T],
+    f_item_size: S,
+    f_item_key: F,
+) -> Vec<(usize, Rect<N>)>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    K: Ord,
+    S: Fn(&T) -> N,
+    F: Fn(&T) -> K,
+{
+    let mut order: Vec<(usize, N, Rect<N>)> = items
+        .iter()
+        .enumerate()
+        .map(|(idx, item)| (idx, f_item_size(item), Rect::from_size(N::zero(), N::zero())))
+        .collect();
+    order.sort_by(|(ia, na, _), (ib, nb, _)| {
+        nb.partial_cmp(na)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| f_item_key(&items[*ia]).cmp(&f_item_key(&items[*ib])))
+    });
+    squarify(rect, &mut order[..], |&(_, n, _)| n, |(_, _, item_r), r| *item_r = r);
+    order.into_iter().map(|(idx, _, r)| (idx, r)).collect()
+}
+
+/// Compute how well tile edges align into rows and columns.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// Counts the pairs of tile edges of the same orientation whose coordinates are
+/// equal within `epsilon`, divided by the number of tiles. The more tiles share
+/// rows and columns, the higher the score.
+///
+/// Returns 0 if there is no tiles.
+///
+/// __Complexity__: `O(items.len()^2)`
+pub fn alignment_score<N, T, F>(items: &[T], f_get_rect: F, epsilon: N) -> N
+where
+    N: Float,
+    F: Fn(&T) -> Rect<N>,
+{
+    if items.is_empty() {
+        return N::zero();
+    }
+    let rects: Vec<Rect<N>> = items.iter().map(f_get_rect).collect();
+    let vertical: Vec<N> = rects.iter().flat_map(|r| [r.x, r.x + r.w]).collect();
+    let horizontal: Vec<N> = rects.iter().flat_map(|r| [r.y, r.y + r.h]).collect();
+    let aligned = |edges: &[N]| {
+        (0..edges.len())
+            .map(|i| {
+                (i + 1..edges.len())
+                    .filter(|&j| i / 2 != j / 2 && (edges[i] - edges[j]).abs() <= epsilon)
+                    .count()
+            })
+            .sum::<usize>()
+    };
+    let count = aligned(&vertical) + aligned(&horizontal);
+    N::from(count).unwrap() / N::from(rects.len()).unwrap()
+}
+
+/// Distribute `items` inside `rect` like [`squarify`], reshaping some tiles to a
+/// fixed aspect ratio while keeping their area.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_aspect` provide the fixed aspect ratio (`w / h`) of an item, if any
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// A reshaped tile is centered across its strip, and the other tiles of the strip
+/// share the remaining strip length in proportion to their size. The tiling is
+/// then approximate: reshaped tiles can overflow their strip and the other tiles
+/// of the strip don't keep their exact area.
+///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_with_aspect<N, T, S, A, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_aspect: A,
+    mut f_item_set_rect: R,
+) where
+    N: Float + NumAssignOps + Sum,
+    S: Fn(&T) -> N,
+    A: Fn(&T) -> Option<N>,
+    R: FnMut(&mut T, Rect<N>),
+{
+    if distribute_zero_area(rect, items, &mut f_item_set_rect)
+        || distribute_single(rect, items, &f_item_size, &mut f_item_set_rect)
+    {
+        return;
+    }
+    let scale = scale(rect, items, &f_item_size);
+    let f_item_size = |item: &T| f_item_size(item) * scale;
+    let mut rect = rect;
+    let mut items = items;
+    while !items.is_empty() {
+        let is_wide = rect.w > rect.h;
+        let side = if is_wide { rect.h } else { rect.w };
+        let (split_idx, size_strip) = squarify_strip(side, items, &f_item_size);
+        let is_last = split_idx == items.len();
+        let (head, tail) = std::mem::take(&mut items).split_at_mut(split_idx);
+        let rect_strip = squarify_split_strip(&mut rect, is_wide, size_strip, is_last);
+        let (length, thickness) =
+            if is_wide { (rect_strip.h, rect_strip.w) } else { (rect_strip.w, rect_strip.h) };
+
+        // Lengths of reshaped tiles along the strip
+        let fixed_length = |item: &T| {
+            f_item_aspect(item).map(|aspect| {
+                let size_item = f_item_size(item);
+                if is_wide {
+                    (size_item / aspect).sqrt()
+                } else {
+                    (size_item * aspect).sqrt()
+                }
+            })
+        };
+        let (mut free_length, mut free_size) = (length, N::zero());
+        for item in head.iter() {
+            match fixed_length(item) {
+                Some(l) => free_length -= l,
+                None => free_size += f_item_size(item),
+            }
+        }
+        let free_length = free_length.max(N::zero());
+
+        let mut pos = if is_wide { rect_strip.y } else { rect_strip.x };
+        for item in head {
+            let size_item = f_item_size(item);
+            let (item_length, item_thickness) = match fixed_length(item) {
+                Some(l) => (l, size_item / l),
+                None => (free_length * size_item / free_size, thickness),
+            };
+            let offset = (thickness - item_thickness) / (N::one() + N::one());
+            let rect_item = if is_wide {
+                Rect { x: rect_strip.x + offset, y: pos, w: item_thickness, h: item_length }
+            } else {
+                Rect { x: pos, y: rect_strip.y + offset, w: item_length, h: item_thickness }
+            };
+            pos += item_length;
+            f_item_set_rect(item, rect_item);
+        }
+        items = tail;
+    }
+}
+
+/// Distribute `(size, rect)` pairs inside `rect` with [`slice`], writing each
+/// distributed Rect into its pair.
+pub fn slice_pairs<N>(rect: Rect<N>, items: &mut [(N, Rect<N>)])
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+{
+    slice(rect, items, |&(n, _)| n, |(_, item_r), r| *item_r = r);
+}
+
+/// Distribute `(size, rect)` pairs inside `rect` with [`dice`], writing each
+/// distributed Rect into its pair.
+pub fn dice_pairs<N>(rect: Rect<N>, items: &mut [(N, Rect<N>)])
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+{
+    dice(rect, items, |&(n, _)| n, |(_, item_r), r| *item_r = r);
+}
+
+/// Distribute `(size, rect)` pairs inside `rect` with [`binary`], writing each
+/// distributed Rect into its pair.
+pub fn binary_pairs<N>(rect: Rect<N>, items: &mut [(N, Rect<N>)])
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+{
+    binary(rect, items, |&(n, _)| n, |(_, item_r), r| *item_r = r);
+}
+
+/// Distribute `(size, rect)` pairs inside `rect` with [`squarify`], writing each
+/// distributed Rect into its pair.
+pub fn squarify_pairs<N>(rect: Rect<N>, items: &mut [(N, Rect<N>)])
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+{
+    squarify(rect, items, |&(n, _)| n, |(_, item_r), r| *item_r = r);
+}
+
+/// Distribute `(size, rect)` pairs inside `rect` with [`ordered_pivot_by_middle`], writing each
+/// distributed Rect into its pair.
+pub fn ordered_pivot_by_middle_pairs<N>(rect: Rect<N>, items: &mut [(N, Rect<N>)])
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+{
+    ordered_pivot_by_middle(rect, items, |&(n, _)| n, |(_, item_r), r| *item_r = r);
+}
+
+/// Distribute `(size, rect)` pairs inside `rect` with [`ordered_pivot_by_size`], writing each
+/// distributed Rect into its pair.
+pub fn ordered_pivot_by_size_pairs<N>(rect: Rect<N>, items: &mut [(N, Rect<N>)])
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+{
+    ordered_pivot_by_size(rect, items, |&(n, _)| n, |(_, item_r), r| *item_r = r);
+}
+
+/// Grid index over a layout for fast hit testing.
+///
+/// The container is divided into about as many cells as there are tiles, each
+/// cell holding the indices of the tiles overlapping it.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex<N> {
+    container: Rect<N>,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+    rects: Vec<Rect<N>>,
+}
+
+impl<N> SpatialIndex<N>
+where
+    N: Float,
+{
+    /// Build the index of the tiles `rects` distributed inside `container`
+    ///
+    /// __Complexity__: `O(rects.len())` for evenly sized tiles
+    pub fn from_layout(container: Rect<N>, rects: &[Rect<N>]) -> Self {
+        let side = (rects.len() as f64).sqrt().ceil().max(1.) as usize;
+        let mut index = SpatialIndex {
+            container,
+            columns: side,
+            rows: side,
+            cells: vec![Vec::new(); side * side],
+            rects: rects.to_vec(),
+        };
+        for (idx, r) in rects.iter().enumerate() {
+            let (c0, c1) = (index.column(r.x), index.column(r.x + r.w));
+            let (r0, r1) = (index.row(r.y), index.row(r.y + r.h));
+            for row in r0..=r1 {
+                for column in c0..=c1 {
+                    index.cells[row * index.columns + column].push(idx);
+                }
+            }
+        }
+        index
+    }
+
+    /// Find the index of the first tile containing the point (`x`, `y`), far edges excluded
+    ///
+    /// __Complexity__: `O(1)` for evenly sized tiles
+    pub fn query_point(&self, x: N, y: N) -> Option<usize> {
+        if !self.container.contains_point(x, y) {
+            return None;
+        }
+        self.cells[self.row(y) * self.columns + self.column(x)]
+            .iter()
+            .copied()
+            .find(|&idx| self.rects[idx].contains_point(x, y))
+    }
+
+    /// Find the indices of the tiles overlapping `rect`, in ascending order
+    ///
+    /// __Complexity__: `O(k)` for evenly sized tiles, where `k` is the number of overlapping tiles
+    pub fn query_rect(&self, rect: &Rect<N>) -> Vec<usize> {
+        let (c0, c1) = (self.column(rect.x), self.column(rect.x + rect.w));
+        let (r0, r1) = (self.row(rect.y), self.row(rect.y + rect.h));
+        let mut found: Vec<usize> = (r0..=r1)
+            .flat_map(|row| (c0..=c1).map(move |column| row * self.columns + column))
+            .flat_map(|cell| self.cells[cell].iter().copied())
+            .filter(|&idx| self.rects[idx].intersection(rect).is_some())
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    /// Column of the cell containing `x`, clamped to the grid
+    fn column(&self, x: N) -> usize {
+        Self::cell(x - self.container.x, self.container.w, self.columns)
+    }
+
+    /// Row of the cell containing `y`, clamped to the grid
+    fn row(&self, y: N) -> usize {
+        Self::cell(y - self.container.y, self.container.h, self.rows)
+    }
+
+    fn cell(offset: N, extent: N, count: usize) -> usize {
+        let cell = (offset / extent * N::from(count).unwrap()).floor();
+        if cell.is_nan() || cell < N::zero() {
+            0
+        } else {
+            cell.to_usize().unwrap_or(count - 1).min(count - 1)
+        }
+    }
+}
+
+/// Distribute `items` inside `rect` like [`squarify`], but forcing the first
+/// `first_k` items into the first strip.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Remaining items are squarified in the area left by the first strip.
+///
+/// Panics if `first_k` is greater than `items.len()`.
+///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_first_strip<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    first_k: usize,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    assert!(first_k <= items.len(), "first_k out of bounds");
+    if distribute_zero_area(rect, items, &mut f_item_set_rect)
+        || distribute_single(rect, items, &f_item_size, &mut f_item_set_rect)
+    {
+        return;
+    }
+    let scale = scale(rect, items, &f_item_size);
+    let f_item_size = |item: &T| f_item_size(item) * scale;
+    let options = SquarifyOptions::new();
+    let mut rect = rect;
+    let mut items = items;
+    if first_k > 0 {
+        let size_strip = items[..first_k].iter().map(&f_item_size).sum();
+        let is_wide = rect.w > rect.h;
+        items = squarify_place_strip(
+            &mut rect,
+            is_wide,
+            items,
+            (first_k, size_strip),
+            &options,
+            &f_item_size,
+            &mut f_item_set_rect,
+        );
+    }
+    _squarify(rect, items, &options, f_item_size, f_item_set_rect, None);
+}
+
+/// Distribute `items` inside `rect` with [`squarify`] by their `before` size and
+/// report the relative change of their `after` size.
+///
+/// - `f_item_size_before` provide the size of an item in the first dataset
+/// - `f_item_size_after` provide the size of an item in the second dataset
+///
+/// Returns the distributed Rect of each item with its relative delta,
+/// `(after - before) / before`, in `items` order. An item with a zero `before` size
+/// has no relative change to report and gets a zero delta.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_diff<N, T, B, A>(
+    rect: Rect<N>,
+    items: &[T],
+    f_item_size_before: B,
+    f_item_size_after: A,
+) -> Vec<(Rect<N>, N)>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    B: Fn(&T) -> N,
+    A: Fn(&T) -> N,
+{
+    let mut diff: Vec<(N, Rect<N>, N)> = items
+        .iter()
+        .map(|item| {
+            let (before, after) = (f_item_size_before(item), f_item_size_after(item));
+            let delta = if before.is_zero() { N::zero() } else { (after - before) / before };
+            (before, Rect::from_size(N::zero(), N::zero()), delta)
+        })
+        .collect();
+    squarify(rect, &mut diff[..], |&(n, _, _)| n, |(_, item_r, _), r| *item_r = r);
+    diff.into_iter().map(|(_, r, delta)| (r, delta)).collect()
+}
+
+/// Map each tile of `old_rects` from `old_container` to `new_container`.
+///
+/// This is much cheaper than distributing items again when only the container
+/// size changes. Tile areas stay proportional, but aspect ratios change with the
+/// container aspect ratio.
+///
+/// __Complexity__: `O(old_rects.len())`
+pub fn resize_layout<N>(
+    old_container: Rect<N>,
+    new_container: Rect<N>,
+    old_rects: &[Rect<N>],
+) -> Vec<Rect<N>>
+where
+    N: NumOps + Copy,
+{
+    let sx = new_container.w / old_container.w;
+    let sy = new_container.h / old_container.h;
+    old_rects
+        .iter()
+        .map(|r| Rect {
+            x: new_container.x + (r.x - old_container.x) * sx,
+            y: new_container.y + (r.y - old_container.y) * sy,
+            w: r.w * sx,
+            h: r.h * sy,
+        })
+        .collect()
+}
+
+/// Distribute `items` inside fixed-height horizontal bands of `rect`, squarifying
+/// the items of each band.
+///
+/// - `band_heights` is the height of each band from top to bottom, expected to sum
+///   to `rect.h`
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Items are assigned in order to the bands, each band receiving the items whose
+/// prefix sum is the closest to its share of the total size. Bands left without
+/// items stay empty, and items aren't distributed if there is no band.
+///
+/// __Complexity__: `O(3⨯items.len() + band_heights.len())`
+pub fn banded<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    band_heights: &[N],
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let sums = prefix_sums(items, &f_item_size);
+    let size_total = sums[items.len()];
+    let height_total: N = band_heights.iter().copied().sum();
+
+    let (mut idx0, mut height) = (0, N::zero());
+    let mut rect_band = Rect { h: N::zero(), ..rect };
+    for (band, &band_height) in band_heights.iter().enumerate() {
+        height += band_height;
+        let idx1 = if band + 1 == band_heights.len() {
+            items.len()
+        } else {
+            closest_prefix_sum(&sums, idx0, size_total * height / height_total)
+        };
+        rect_band.h = band_height;
+        if idx1 > idx0 {
+            squarify(rect_band, &mut items[idx0..idx1], &f_item_size, &mut f_item_set_rect);
+        }
+        rect_band.y += band_height;
+        idx0 = idx1;
+    }
+}
+
+/// A distributed layout: the tiles of some items inside a container
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout<N> {
+    /// Rect the tiles were distributed in
+    pub container: Rect<N>,
+    /// Tile of each item, in items order
+    pub rects: Vec<Rect<N>>,
+}
+
+impl<N> Layout<N>
+where
+    N: NumOps + PartialOrd + Copy,
+{
+    /// Create a layout from the tiles of `items` distributed inside `container`
+    ///
+    /// - `f_get_rect` provide the distributed Rect of an item
+    pub fn from_items<T, F>(container: Rect<N>, items: &[T], f_get_rect: F) -> Self
+    where
+        F: Fn(&T) -> Rect<N>,
+    {
+        Layout { container, rects: items.iter().map(f_get_rect).collect() }
+    }
+
+    /// Iterate over the tiles intersecting `viewport`, with their index
+    ///
+    /// __Complexity__: `O(rects.len())`, see [`SpatialIndex`] for large layouts
+    pub fn visible<'a>(
+        &'a self,
+        viewport: &'a Rect<N>,
+    ) -> impl Iterator<Item = (usize, Rect<N>)> + 'a {
+        self.rects
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(move |(_, r)| r.intersection(viewport).is_some())
+    }
+}
+
+/// Scalar type of a [`Layout`] that can be encoded with [`Layout::to_bytes`]
+pub trait LayoutScalar: Copy {
+    /// Tag identifying the scalar type in the encoded header
+    const TAG: u8;
+    /// Encoded size in bytes
+    const SIZE: usize;
+
+    /// Append the little-endian bytes of this scalar to `out`
+    fn write_le(self, out: &mut Vec<u8>);
+
+    /// Read a scalar from the first [`LayoutScalar::SIZE`] little-endian `bytes`
+    fn read_le(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_layout_scalar {
+    ($($t:ty => $tag:expr),*) => {
+        $(
+            impl LayoutScalar for $t {
+                const TAG: u8 = $tag;
+                const SIZE: usize = std::mem::size_of::<$t>();
+
+                fn write_le(self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn read_le(bytes: &[u8]) -> Self {
+                    <$t>::from_le_bytes(bytes[..Self::SIZE].try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+impl_layout_scalar!(f32 => 1, f64 => 2, i32 => 3, i64 => 4, u32 => 5, u64 => 6);
+
+/// Error returned by [`Layout::from_bytes`] when the bytes aren't an encoded layout
+/// of the expected scalar type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecodeError;
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid encoded layout")
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl<N> Layout<N>
+where
+    N: LayoutScalar,
+{
+    /// Encode this layout in a compact binary format, for caching.
+    ///
+    /// The header is the scalar type tag (1 byte) and the number of tiles
+    /// (4 bytes), followed by the `x`, `y`, `w` and `h` of the container and of each
+    /// tile, all little-endian.
+    ///
+    /// Panics if there are more than `u32::MAX` tiles.
+    ///
+    /// __Complexity__: `O(rects.len())`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let count = u32::try_from(self.rects.len()).expect("too many tiles to encode");
+        let mut out = Vec::with_capacity(5 + (self.rects.len() + 1) * 4 * N::SIZE);
+        out.push(N::TAG);
+        out.extend_from_slice(&count.to_le_bytes());
+        for r in std::iter::once(&self.container).chain(&self.rects) {
+            for v in [r.x, r.y, r.w, r.h] {
+                v.write_le(&mut out);
+            }
+        }
+        out
+    }
+
+    /// Decode a layout encoded by [`Layout::to_bytes`].
+    ///
+    /// Fails if the scalar type tag doesn't match `N` or if the length of `bytes`
+    /// doesn't match the number of tiles.
+    ///
+    /// __Complexity__: `O(rects.len())`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (&tag, rest) = bytes.split_first().ok_or(DecodeError)?;
+        if tag != N::TAG || rest.len() < 4 {
+            return Err(DecodeError);
+        }
+        let (count, rest) = rest.split_at(4);
+        let count = u32::from_le_bytes(count.try_into().unwrap()) as usize;
+        let rect_size = 4 * N::SIZE;
+        if count.checked_add(1).and_then(|n| n.checked_mul(rect_size)) != Some(rest.len()) {
+            return Err(DecodeError);
+        }
+        let mut rects = rest.chunks_exact(rect_size).map(|chunk| Rect {
+            x: N::read_le(chunk),
+            y: N::read_le(&chunk[N::SIZE..]),
+            w: N::read_le(&chunk[2 * N::SIZE..]),
+            h: N::read_le(&chunk[3 * N::SIZE..]),
+        });
+        let container = rects.next().unwrap();
+        Ok(Layout { container, rects: rects.collect() })
+    }
+}
+
+/// Distribute `items` inside `rect` with [`squarify`], handing each tile to `visitor`
+/// as soon as it is computed.
+///
+/// - `f_item_size` provide the size of an item
+/// - `visitor` receive the item index and its distributed Rect.
+///   Called once for each item, in increasing index order.
+///
+/// Tiles aren't stored, so callers can render them as they're produced. Only a
+/// reference to each item is.
+///
+/// __Complexity__: `O(4⨯items.len())`
+pub fn squarify_visit<N, T, S, V>(rect: Rect<N>, items: &[T], f_item_size: S, mut visitor: V)
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    V: FnMut(usize, Rect<N>),
+{
+    let mut refs: Vec<&T> = items.iter().collect();
+    let mut idx = 0;
+    squarify(
+        rect,
+        &mut refs,
+        |item| f_item_size(item),
+        |_, r| {
+            visitor(idx, r);
+            idx += 1;
+        },
+    );
+}
+
+/// Distribute `items` inside `rect` like [`squarify`], but trying to get the aspect
+/// ratio of tiles as close as possible to the aspect ratio of `rect` instead of 1.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Very wide or tall containers get tiles of the same shape, which use the
+/// container extent better than square tiles.
+///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_container_aspect<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    if distribute_zero_area(rect, items, &mut f_item_set_rect)
+        || distribute_single(rect, items, &f_item_size, &mut f_item_set_rect)
+    {
+        return;
+    }
+    let scale = scale(rect, items, &f_item_size);
+    let f_item_size = |item: &T| f_item_size(item) * scale;
+    let options = SquarifyOptions::new();
+    let (mut rect, mut items) = (rect, items);
+    let (w, h) = (rect.w, rect.h);
+    while !items.is_empty() {
+        let is_wide = rect.w > rect.h;
+        // Columns stack tiles vertically: thickness is the tile width
+        let (side, weights) = if is_wide { (rect.h, (h, w)) } else { (rect.w, (w, h)) };
+        let strip = squarify_strip_weighted(side, items, &f_item_size, weights, N::zero());
+        items = squarify_place_strip(
+            &mut rect,
+            is_wide,
+            items,
+            strip,
+            &options,
+            &f_item_size,
+            &mut f_item_set_rect,
+        );
+    }
+}
+
+/// Distribute groups of `items` side by side along the longest side of `rect`,
+/// separated by gutters, the items of each group being squarified in their region.
+///
+/// - `f_item_group` provide the group of an item, groups are runs of consecutive
+///   items of the same group
+/// - `f_gutter` provide the gutter of a group from its total size
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// The gap between two adjacent groups is the mean of their gutters. Gaps shrink
+/// the area available to groups, and are scaled down if they don't fit in `rect`
+/// so group regions never get a negative size. The extent of a group region is
+/// proportional to its total size.
+///
+/// __Complexity__: `O(5⨯items.len())`
+pub fn squarify_grouped<N, T, K, G, F, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_group: G,
+    f_gutter: F,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    K: PartialEq,
+    G: Fn(&T) -> K,
+    F: Fn(N) -> N,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let mut groups: Vec<(Range<usize>, N)> = Vec::new();
+    let mut start = 0;
+    for idx in 1..=items.len() {
+        if idx == items.len() || f_item_group(&items[idx]) != f_item_group(&items[start]) {
+            groups.push((start..idx, items[start..idx].iter().map(&f_item_size).sum()));
+            start = idx;
+        }
+    }
+
+    let two = N::one() + N::one();
+    let gaps: Vec<N> =
+        groups.windows(2).map(|w| (f_gutter(w[0].1) + f_gutter(w[1].1)) / two).collect();
+    let gap_total: N = gaps.iter().copied().sum();
+    let is_wide = rect.w > rect.h;
+    let length = if is_wide { rect.w } else { rect.h };
+    let gap_scale = if gap_total > length { length / gap_total } else { N::one() };
+    let available = length - gap_total * gap_scale;
+    let size_total: N = groups.iter().map(|(_, size)| *size).sum();
+
+    let mut pos = if is_wide { rect.x } else { rect.y };
+    for (idx, (range, size)) in groups.into_iter().enumerate() {
+        let extent = available * size / size_total;
+        let region = if is_wide {
+            Rect { x: pos, w: extent, ..rect }
+        } else {
+            Rect { y: pos, h: extent, ..rect }
+        };
+        squarify(region, &mut items[range], &f_item_size, &mut f_item_set_rect);
+        pos += extent;
+        if let Some(&gap) = gaps.get(idx) {
+            pos += gap * gap_scale;
+        }
+    }
+}
+
+/// Compute the area-weighted centroid of the tiles of `items`.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// Useful to anchor the label of a group of tiles.
+/// Returns `(0, 0)` if the tiles have no area.
+///
+/// __Complexity__: `O(items.len())`
+pub fn weighted_centroid<N, T, F>(items: &[T], f_get_rect: F) -> (N, N)
+where
+    N: NumOps + Zero + One + Copy,
+    F: Fn(&T) -> Rect<N>,
+{
+    let two = N::one() + N::one();
+    let (mut area, mut x, mut y) = (N::zero(), N::zero(), N::zero());
+    for r in items.iter().map(f_get_rect) {
+        let a = r.w * r.h;
+        area = area + a;
+        x = x + (r.x + r.w / two) * a;
+        y = y + (r.y + r.h / two) * a;
+    }
+    if area.is_zero() {
+        (N::zero(), N::zero())
+    } else {
+        (x / area, y / area)
+    }
+}
+
+/// Distribute at most `max_strips` strips of `items` inside `rect` like [`squarify`].
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each placed item and in a stable order.
+///
+/// Returns the range of the items left unplaced and the remaining area of `rect`.
+/// Distributing them with [`squarify`] continues the layout.
+///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_limited<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    max_strips: usize,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) -> (Range<usize>, Rect<N>)
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let len = items.len();
+    if max_strips == 0 {
+        return (0..len, rect);
+    }
+    if distribute_zero_area(rect, items, &mut f_item_set_rect) {
+        return (len..len, rect);
+    }
+    if distribute_single(rect, items, &f_item_size, &mut f_item_set_rect) {
+        return (len..len, Rect { w: N::zero(), h: N::zero(), ..rect });
+    }
+    let scale = scale(rect, items, &f_item_size);
+    let f_item_size = |item: &T| f_item_size(item) * scale;
+    let options = SquarifyOptions::new();
+    let (mut rect, mut items) = (rect, items);
+    for _ in 0..max_strips {
+        if items.is_empty() {
+            break;
+        }
+        let is_wide = rect.w > rect.h;
+        let side = if is_wide { rect.h } else { rect.w };
+        let strip = squarify_strip(side, items, &f_item_size);
+        items = squarify_place_strip(
+            &mut rect,
+            is_wide,
+            items,
+            strip,
+            &options,
+            &f_item_size,
+            &mut f_item_set_rect,
+        );
+    }
+    (len - items.len()..len, rect)
+}
+
+/// Count the tiles of `items` in `bins` aspect ratio ranges of equal width, from
+/// 1:1 up to the worst aspect ratio.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// Tiles with the worst aspect ratio go to the last bin. If all tiles are squares,
+/// they all go to the first bin.
+///
+/// Panics if `bins` is zero.
+///
+/// __Complexity__: `O(2⨯items.len())`
+pub fn aspect_histogram<N, T, F>(items: &[T], f_get_rect: F, bins: usize) -> Vec<usize>
+where
+    N: Float,
+    F: Fn(&T) -> Rect<N>,
+{
+    assert!(bins > 0, "bins must not be zero");
+    let aspects: Vec<N> = items.iter().map(|item| f_get_rect(item).aspect_ratio()).collect();
+    let worst = aspects.iter().copied().fold(N::one(), N::max);
+    let mut histogram = vec![0; bins];
+    for aspect in aspects {
+        let bin = if worst > N::one() {
+            ((aspect - N::one()) / (worst - N::one()) * N::from(bins).unwrap())
+                .to_usize()
+                .unwrap_or(0)
+                .min(bins - 1)
+        } else {
+            0
+        };
+        histogram[bin] += 1;
+    }
+    histogram
+}
+
+/// Distribute `items` inside `rect` along `axis` like [`fill`], but without scaling
+/// item sizes to the `rect` area.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Returns the leftover area of `rect` after the last tile, empty if items
+/// fill or overflow `rect`. Overflowing tiles extend past `rect`.
+///
+/// __Complexity__: `O(items.len())`
+pub fn fill_fit<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    axis: Axis,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) -> Rect<N>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let is_vertical = axis == Axis::Vertical;
+    let mut end = if is_vertical { rect.y } else { rect.x };
+    _fill_fit(rect, items, axis, f_item_size, |item, r| {
+        end = if is_vertical { r.y + r.h } else { r.x + r.w };
+        f_item_set_rect(item, r)
+    });
+    if is_vertical {
+        let h = rect.y + rect.h - end;
+        Rect { y: end, h: if h > N::zero() { h } else { N::zero() }, ..rect }
+    } else {
+        let w = rect.x + rect.w - end;
+        Rect { x: end, w: if w > N::zero() { w } else { N::zero() }, ..rect }
+    }
+}
+
+/// Distribute `items` inside `rect` vertically without scaling item sizes, see [`fill_fit`].
+///
+/// Returns the leftover area of `rect` below the last tile.
+///
+/// __Complexity__: `O(items.len())`
+pub fn slice_fit<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_set_rect: R,
+) -> Rect<N>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    fill_fit(rect, items, Axis::Vertical, f_item_size, f_item_set_rect)
+}
+
+/// Distribute `items` inside `rect` horizontally without scaling item sizes, see [`fill_fit`].
+///
+/// Returns the leftover area of `rect` right of the last tile.
+///
+/// __Complexity__: `O(items.len())`
+pub fn dice_fit<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_set_rect: R,
+) -> Rect<N>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    fill_fit(rect, items, Axis::Horizontal, f_item_size, f_item_set_rect)
+}
+
+/// Distribute `items` inside `rect` along `axis` like [`fill_fit`], filling the
+/// leftover area with a generated filler tile.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+/// - `f_remainder` receive the filler Rect, after every item.
+///   Only called if items don't fill `rect`.
+///
+/// __Complexity__: `O(items.len())`
+pub fn fill_fit_remainder<N, T, S, R, F>(
+    rect: Rect<N>,
+    items: &mut [T],
+    axis: Axis,
+    f_item_size: S,
+    f_item_set_rect: R,
+    f_remainder: F,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+    F: FnOnce(Rect<N>),
+{
+    let leftover = fill_fit(rect, items, axis, f_item_size, f_item_set_rect);
+    if leftover.w > N::zero() && leftover.h > N::zero() {
+        f_remainder(leftover);
+    }
+}
+
+/// A tree map algorithm, to pick one at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// [`slice`]
+    Slice,
+    /// [`dice`]
+    Dice,
+    /// [`binary`]
+    Binary,
+    /// [`squarify`]
+    Squarify,
+    /// [`ordered_pivot_by_middle`]
+    OrderedPivotByMiddle,
+    /// [`ordered_pivot_by_size`]
+    OrderedPivotBySize,
+}
+
+impl Algorithm {
+    /// Every algorithm
+    pub const ALL: [Algorithm; 6] = [
+        Algorithm::Slice,
+        Algorithm::Dice,
+        Algorithm::Binary,
+        Algorithm::Squarify,
+        Algorithm::OrderedPivotByMiddle,
+        Algorithm::OrderedPivotBySize,
+    ];
+
+    /// Distribute `items` inside `rect` with this algorithm.
+    ///
+    /// - `f_item_size` provide the size of an item
+    /// - `f_item_set_rect` receive the item distributed Rect.
+    ///   Called once for each item and in a stable order.
+    pub fn layout<N, T, S, R>(
+        self,
+        rect: Rect<N>,
+        items: &mut [T],
+        f_item_size: S,
+        f_item_set_rect: R,
+    ) where
+        N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+        S: Fn(&T) -> N,
+        R: FnMut(&mut T, Rect<N>),
+    {
+        self._layout(rect, items, f_item_size, f_item_set_rect, None);
+    }
+
+    fn _layout<N, T, S, R>(
+        self,
+        rect: Rect<N>,
+        items: &mut [T],
+        f_item_size: S,
+        f_item_set_rect: R,
+        f_tie_cost: TieCost<T, N>,
+    ) where
+        N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+        S: Fn(&T) -> N,
+        R: FnMut(&mut T, Rect<N>),
+    {
+        match self {
+            Algorithm::Slice => slice(rect, items, f_item_size, f_item_set_rect),
+            Algorithm::Dice => dice(rect, items, f_item_size, f_item_set_rect),
+            Algorithm::Binary => {
+                _binary_with_stats(rect, items, f_item_size, f_item_set_rect, f_tie_cost);
+            }
+            Algorithm::Squarify => {
+                let options = &SquarifyOptions::new();
+                _squarify_with(rect, items, options, f_item_size, f_item_set_rect, f_tie_cost);
+            }
+            Algorithm::OrderedPivotByMiddle => {
+                let (f_size, f_set_rect) = (f_item_size, f_item_set_rect);
+                _ordered_pivot_by_middle_with_stats(rect, items, f_size, f_set_rect, f_tie_cost);
+            }
+            Algorithm::OrderedPivotBySize => {
+                let (f_size, f_set_rect) = (f_item_size, f_item_set_rect);
+                _ordered_pivot_by_size_with_stats(rect, items, f_size, f_set_rect, f_tie_cost);
+            }
+        }
+    }
+}
+
+/// Distribute `items` inside `rect` with the algorithm of `candidates` giving the
+/// lowest [`average_aspect_ratio`].
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Returns the selected algorithm, the first one of `candidates` on ties.
+///
+/// Panics if `candidates` is empty.
+///
+/// __Complexity__: sum of the `candidates` complexities
+pub fn best_fit<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    candidates: &[Algorithm],
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) -> Algorithm
+where
+    N: Float + NumAssignOps + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    assert!(!candidates.is_empty(), "candidates must not be empty");
+    let rect_zero = Rect::from_size(N::zero(), N::zero());
+    let (mut best, mut best_score, mut best_tiles) = (candidates[0], None, Vec::new());
+    for &algorithm in candidates {
+        let mut tiles: Vec<(usize, Rect<N>)> =
+            (0..items.len()).map(|idx| (idx, rect_zero)).collect();
+        algorithm.layout(
+            rect,
+            &mut tiles[..],
+            |&(idx, _)| f_item_size(&items[idx]),
+            |(_, item_r), r| *item_r = r,
+        );
+        let score = average_aspect_ratio(&tiles, |&(_, r)| r);
+        if best_score.is_none() || Some(score) < best_score {
+            best = algorithm;
+            best_score = Some(score);
+            best_tiles = tiles;
+        }
+    }
+    for (item, (_, r)) in items.iter_mut().zip(best_tiles) {
+        f_item_set_rect(item, r);
+    }
+    best
+}
+
+/// Compute the smallest rect containing every tile of `items`.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// Returns `None` if there is no tiles.
+///
+/// __Complexity__: `O(items.len())`
+pub fn bounding_box<N, T, F>(items: &[T], f_get_rect: F) -> Option<Rect<N>>
+where
+    N: NumOps + PartialOrd + Copy,
+    F: Fn(&T) -> Rect<N>,
+{
+    items.iter().map(f_get_rect).reduce(|bounds, r| bounds.union(&r))
+}
+
+/// Translate every tile of `items` so their bounding box is centered in `container`.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+/// - `f_set_rect` receive the translated Rect of an item.
+///   Called once for each item and in a stable order.
+///
+/// Useful when tiles don't fill the container, after padding for example.
+///
+/// __Complexity__: `O(2⨯items.len())`
+pub fn center_in<N, T, F, G>(container: Rect<N>, items: &mut [T], f_get_rect: F, mut f_set_rect: G)
+where
+    N: NumOps + PartialOrd + One + Copy,
+    F: Fn(&T) -> Rect<N>,
+    G: FnMut(&mut T, Rect<N>),
+{
+    let bounds = match bounding_box(items, &f_get_rect) {
+        Some(bounds) => bounds,
+        None => return,
+    };
+    let two = N::one() + N::one();
+    let dx = container.x + (container.w - bounds.w) / two - bounds.x;
+    let dy = container.y + (container.h - bounds.h) / two - bounds.y;
+    for item in items.iter_mut() {
+        let r = f_get_rect(item).translate(dx, dy);
+        f_set_rect(item, r);
+    }
+}
+
+/// Lay out a legend of `n_items` rows stacked vertically with [`slice`] inside
+/// `legend_rect`.
+///
+/// Returns for each row a square swatch Rect of side `swatch`, at the start of the
+/// row and vertically centered, and the label Rect filling the rest of the row.
+///
+/// __Complexity__: `O(2⨯n_items)`
+pub fn legend_layout<N>(legend_rect: Rect<N>, n_items: usize, swatch: N) -> Vec<(Rect<N>, Rect<N>)>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+{
+    let two = N::one() + N::one();
+    let mut rows = vec![Rect::from_size(N::zero(), N::zero()); n_items];
+    slice(legend_rect, &mut rows[..], |_| N::one(), |row, r| *row = r);
+    rows.into_iter()
+        .map(|row| {
+            let rect_swatch =
+                Rect { x: row.x, y: row.y + (row.h - swatch) / two, w: swatch, h: swatch };
+            let rect_label = Rect { x: row.x + swatch, w: row.w - swatch, ..row };
+            (rect_swatch, rect_label)
+        })
+        .collect()
+}
+
+/// Compute the size of each item as a fraction of the largest size, in `[0, 1]`.
+///
+/// - `f_item_size` provide the size of an item
+///
+/// Useful to map tiles to an opacity or a color intensity. Returns zeros if the
+/// largest size isn't positive.
+///
+/// __Complexity__: `O(2⨯items.len())`
+pub fn relative_sizes<N, T, S>(items: &[T], f_item_size: S) -> Vec<N>
+where
+    N: NumOps + PartialOrd + Zero + Copy,
+    S: Fn(&T) -> N,
+{
+    let max =
+        items.iter().map(&f_item_size).fold(N::zero(), |max, n| if n > max { n } else { max });
+    items
+        .iter()
+        .map(|item| if max > N::zero() { f_item_size(item) / max } else { N::zero() })
+        .collect()
+}
+
+/// Distribute `items` inside `rect` with [`squarify`], every item getting the same
+/// area regardless of its size.
+///
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Tiles are packed in squarified strips rather than a regular grid.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_equal<N, T, R>(rect: Rect<N>, items: &mut [T], f_item_set_rect: R)
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    R: FnMut(&mut T, Rect<N>),
+{
+    squarify(rect, items, |_| N::one(), f_item_set_rect);
+}
+
+/// Distribute `items` row-major inside `rect` in rows of `cols` items.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Rows have the same height, and the width of a cell is proportional to the item
+/// size within its row. The last row holds the remaining items.
+///
+/// Panics if `cols` is zero.
+///
+/// __Complexity__: `O(2⨯items.len())`
+pub fn grid_weighted<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    cols: usize,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum + NumCast,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    assert!(cols > 0, "cols must not be zero");
+    let rows = items.chunks(cols).len();
+    if rows == 0 {
+        return;
+    }
+    let row_height = rect.h / N::from(rows).unwrap();
+    let mut row_rect = Rect { h: row_height, ..rect };
+    for row in items.chunks_mut(cols) {
+        dice(row_rect, row, &f_item_size, &mut f_item_set_rect);
+        row_rect.y += row_height;
+    }
+}
+
+/// Change of an item between two layouts, see [`classify_changes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Change<K> {
+    /// The item is only in the new layout
+    Added(K),
+    /// The item is only in the old layout
+    Removed(K),
+    /// The item tile changed size
+    Resized(K),
+    /// The item tile moved without changing size
+    Moved(K),
+}
+
+/// Classify the changes of items between the `old` and `new` layouts, matching
+/// items by key.
+///
+/// - `f_key` provide the stable key of an item
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// Unchanged items are omitted. Changes of `new` items come first in `new` order,
+/// followed by removed items in `old` order.
+///
+/// __Complexity__: `O(old.len() + new.len())`
+pub fn classify_changes<N, T, K, F, G>(
+    old: &[T],
+    new: &[T],
+    f_key: F,
+    f_get_rect: G,
+) -> Vec<Change<K>>
+where
+    N: PartialEq,
+    K: Eq + Hash,
+    F: Fn(&T) -> K,
+    G: Fn(&T) -> Rect<N>,
+{
+    let mut old_rects: HashMap<K, Rect<N>> =
+        old.iter().map(|item| (f_key(item), f_get_rect(item))).collect();
+    let mut changes = Vec::new();
+    for item in new {
+        let key = f_key(item);
+        match old_rects.remove(&key) {
+            None => changes.push(Change::Added(key)),
+            Some(old_r) => {
+                let r = f_get_rect(item);
+                if old_r.w != r.w || old_r.h != r.h {
+                    changes.push(Change::Resized(key));
+                } else if old_r.x != r.x || old_r.y != r.y {
+                    changes.push(Change::Moved(key));
+                }
+            }
+        }
+    }
+    for item in old {
+        let key = f_key(item);
+        if old_rects.remove(&key).is_some() {
+            changes.push(Change::Removed(key));
+        }
+    }
+    changes
+}
+
+/// Distribute `items` with [`squarify`] inside the largest square centered in
+/// `container`.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_inscribed<N, T, S, R>(
+    container: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let two = N::one() + N::one();
+    let side = if container.w < container.h { container.w } else { container.h };
+    let square = Rect {
+        x: container.x + (container.w - side) / two,
+        y: container.y + (container.h - side) / two,
+        w: side,
+        h: side,
+    };
+    squarify(square, items, f_item_size, f_item_set_rect);
+}
+
+/// Edges of a tile lying on the container boundary, see [`squarify_with_borders`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BorderEdges {
+    pub top: bool,
+    pub right: bool,
+    pub bottom: bool,
+    pub left: bool,
+}
+
+/// Distribute `items` inside `rect` like [`squarify`], also reporting which edges
+/// of each tile lie on the boundary of `rect`.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect and its boundary edges.
+///   Called once for each item and in a stable order.
+///
+/// Edges are found from the strips structure, not by comparing coordinates, so
+/// rounding errors don't matter. Useful to only draw borders on edges shared with
+/// a neighbor and avoid double-thickness lines.
+///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_with_borders<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>, BorderEdges),
+{
+    let mut edges = BorderEdges { top: true, right: true, bottom: true, left: true };
+    let mut f_item_set_rect_edges = |item: &mut T, r| f_item_set_rect(item, r, edges);
+    if distribute_zero_area(rect, items, &mut f_item_set_rect_edges)
+        || distribute_single(rect, items, &f_item_size, &mut f_item_set_rect_edges)
+    {
+        return;
+    }
+    let scale = scale(rect, items, &f_item_size);
+    let f_item_size = |item: &T| f_item_size(item) * scale;
+    let (mut rect, mut items) = (rect, items);
+    while !items.is_empty() {
+        let is_wide = rect.w > rect.h;
+        let side = if is_wide { rect.h } else { rect.w };
+        let (split_idx, size_strip) = squarify_strip(side, items, &f_item_size);
+        let is_last = split_idx == items.len();
+        let (head, tail) = std::mem::take(&mut items).split_at_mut(split_idx);
+        let rect_strip = squarify_split_strip(&mut rect, is_wide, size_strip, is_last);
+
+        // The strip is at the start of the remaining area and spans it
+        let mut strip_edges = edges;
+        if is_wide {
+            strip_edges.right &= is_last;
+            edges.left = false;
+        } else {
+            strip_edges.bottom &= is_last;
+            edges.top = false;
+        }
+        let (len, mut idx) = (head.len(), 0);
+        let axis = if is_wide { Axis::Vertical } else { Axis::Horizontal };
+        _fill(rect_strip, head, axis, f_item_size, |item, r| {
+            let mut item_edges = strip_edges;
+            if is_wide {
+                item_edges.top &= idx == 0;
+                item_edges.bottom &= idx + 1 == len;
+            } else {
+                item_edges.left &= idx == 0;
+                item_edges.right &= idx + 1 == len;
+            }
+            idx += 1;
+            f_item_set_rect(item, r, item_edges);
+        });
+        items = tail;
+    }
+}
+
+/// Distribute `items` inside `rect` with [`squarify`], reporting placeholder items
+/// through a separate callback so they can be rendered as empty frames.
+///
+/// - `f_item_size` provide the size of an item, placeholders included
+/// - `f_item_is_placeholder` tell if an item is a placeholder
+/// - `f_item_set_rect` receive the distributed Rect of an item that isn't a placeholder
+/// - `f_placeholder_set_rect` receive the distributed Rect of a placeholder
+///
+/// Each item is passed to one of the callbacks exactly once, in a stable order.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_with_placeholders<N, T, S, P, R, Q>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_is_placeholder: P,
+    mut f_item_set_rect: R,
+    mut f_placeholder_set_rect: Q,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    P: Fn(&T) -> bool,
+    R: FnMut(&mut T, Rect<N>),
+    Q: FnMut(&mut T, Rect<N>),
+{
+    squarify(rect, items, f_item_size, |item, r| {
+        if f_item_is_placeholder(item) {
+            f_placeholder_set_rect(item, r);
+        } else {
+            f_item_set_rect(item, r);
+        }
+    });
+}
+
+/// Distribute `items` inside `rect` with an algorithm picked from the dataset
+/// characteristics, without running every algorithm like [`best_fit`].
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Decision rules, in order:
+///
+/// - up to 2 items: [`dice`] if `rect` is wider than tall, [`slice`] otherwise, which
+///   split the longest side
+/// - items not sorted by size in descending order, a hint that their order is
+///   meaningful: [`binary`], which is less sensitive to the input order
+/// - otherwise: [`squarify`]
+///
+/// Returns the selected algorithm.
+///
+/// __Complexity__: `O(items.len())` plus the selected algorithm complexity
+pub fn auto<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_set_rect: R,
+) -> Algorithm
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let algorithm = if items.len() <= 2 {
+        if rect.w > rect.h {
+            Algorithm::Dice
+        } else {
+            Algorithm::Slice
+        }
+    } else if !is_sorted_desc_by_size(items, &f_item_size) {
+        Algorithm::Binary
+    } else {
+        Algorithm::Squarify
+    };
+    algorithm.layout(rect, items, f_item_size, f_item_set_rect);
+    algorithm
+}
+
+/// An annular sector, see [`sunburst`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Arc<N> {
+    pub inner_radius: N,
+    pub outer_radius: N,
+    pub start_angle: N,
+    pub end_angle: N,
+}
+
+fn _sunburst<N, T, S, C, R>(
+    (start_angle, end_angle): (N, N),
+    depth: usize,
+    ring_width: N,
+    items: &mut [T],
+    f_item_size: &S,
+    f_children: &C,
+    f_item_set_arc: &mut R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum + NumCast,
+    S: Fn(&T) -> N,
+    C: Fn(&mut T) -> &mut [T],
+    R: FnMut(&mut T, usize, Arc<N>),
+{
+    let size_total: N = items.iter().map(f_item_size).sum();
+    let inner_radius = ring_width * N::from(depth).unwrap();
+    let outer_radius = inner_radius + ring_width;
+    let mut angle = start_angle;
+    let mut it = items.iter_mut();
+    while let Some(item) = it.next() {
+        let end = if it.len() > 0 {
+            angle + (end_angle - start_angle) * f_item_size(item) / size_total
+        } else {
+            end_angle
+        };
+        let arc = Arc { inner_radius, outer_radius, start_angle: angle, end_angle: end };
+        f_item_set_arc(item, depth, arc);
+        let children = f_children(item);
+        if !children.is_empty() {
+            _sunburst(
+                (angle, end),
+                depth + 1,
+                ring_width,
+                children,
+                f_item_size,
+                f_children,
+                f_item_set_arc,
+            );
+        }
+        angle = end;
+    }
+}
+
+/// Distribute a tree of `items` in concentric rings of `ring_width`, the radial
+/// analog of [`squarify_tree`].
+///
+/// - `angles` is the `(start, end)` angular span of the root nodes, `(0, 2π)` for
+///   a full circle
+/// - `f_item_size` provide the size of a node, children included
+/// - `f_children` provide the children of a node
+/// - `f_item_set_arc` receive the node depth and its Arc.
+///   Called once for each node, parents before their children, and in a stable order.
+///
+/// Nodes at depth `d` lie in the ring from `d⨯ring_width` to `(d + 1)⨯ring_width`.
+/// The angular span of a node is divided between its children proportionally to
+/// their size.
+///
+/// __Complexity__: `O(2⨯nodes)`
+pub fn sunburst<N, T, S, C, R>(
+    angles: (N, N),
+    ring_width: N,
+    items: &mut [T],
+    f_item_size: S,
+    f_children: C,
+    mut f_item_set_arc: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum + NumCast,
+    S: Fn(&T) -> N,
+    C: Fn(&mut T) -> &mut [T],
+    R: FnMut(&mut T, usize, Arc<N>),
+{
+    _sunburst(angles, 0, ring_width, items, &f_item_size, &f_children, &mut f_item_set_arc);
+}
+
+/// Suggest the `(w, h)` dimensions of a container for `items`, so equal-sized tiles
+/// of aspect ratio `target_aspect` (`w / h`) would fit in a grid.
+///
+/// - `f_item_size` provide the size of an item
+///
+/// The container area is the total size of `items`. Its aspect ratio is the one of
+/// a grid of `cols⨯rows` tiles of `target_aspect` holding every item, the grid being
+/// as close to a square as possible.
+///
+/// Returns `(0, 0)` if there is no items.
+///
+/// __Complexity__: `O(items.len())`
+pub fn suggest_container<N, T, S>(items: &[T], f_item_size: S, target_aspect: N) -> (N, N)
+where
+    N: Float,
+    S: Fn(&T) -> N,
+{
+    if items.is_empty() {
+        return (N::zero(), N::zero());
+    }
+    let area = items.iter().map(f_item_size).fold(N::zero(), |a, b| a + b);
+    let n = items.len();
+    let cols =
+        (N::from(n).unwrap() / target_aspect).sqrt().round().to_usize().unwrap_or(1).clamp(1, n);
+    let rows = items.chunks(cols).len();
+    let aspect = target_aspect * N::from(cols).unwrap() / N::from(rows).unwrap();
+    ((area * aspect).sqrt(), (area / aspect).sqrt())
+}
+
+/// Distribute `items` inside `rect` with [`squarify`], leaving `holes` empty.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// `rect` minus `holes` is decomposed with [`Rect::subtract`] into rectangular
+/// regions, then items are distributed across them like [`squarify_multi`].
+/// Items get zero-area rects at the origin of `rect` if `holes` cover it entirely.
+///
+/// __Complexity__: `O(4⨯items.len() + 4^holes.len())`
+pub fn squarify_avoiding<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    holes: &[Rect<N>],
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let mut regions = vec![rect];
+    for hole in holes {
+        regions = regions.iter().flat_map(|region| region.subtract(hole)).flatten().collect();
+    }
+    if regions.is_empty() {
+        regions.push(Rect { w: N::zero(), h: N::zero(), ..rect });
+    }
+    squarify_multi(&regions, items, f_item_size, |item, _, r| f_item_set_rect(item, r));
+}
+
+/// Region of the recursive partition of [`binary`], see [`binary_tree`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BinaryNode<N> {
+    /// The rect of the item at `index`
+    Leaf { index: usize, rect: Rect<N> },
+    /// A rect split in 2 areas
+    Split { rect: Rect<N>, left: Box<BinaryNode<N>>, right: Box<BinaryNode<N>> },
+}
+
+impl<N: Copy> BinaryNode<N> {
+    /// The rect of this region
+    pub fn rect(&self) -> Rect<N> {
+        match self {
+            BinaryNode::Leaf { rect, .. } | BinaryNode::Split { rect, .. } => *rect,
+        }
+    }
+}
+
+fn _binary_tree<N>(rect: Rect<N>, sums: &[N], index: usize, offset: N, value: N) -> BinaryNode<N>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+{
+    if sums.len() == 1 {
+        return BinaryNode::Leaf { index, rect };
+    }
+
+    let (mid, rect, lrect, rrect, left) = if value.is_zero() {
+        // Don't divide by a zero subtree size, its items have no area
+        let rect_zero = Rect { w: N::zero(), h: N::zero(), ..rect };
+        (sums.len() / 2, rect_zero, rect_zero, rect_zero, N::zero())
+    } else {
+        let (mid, left, lrect, rrect) = binary_split(rect, rect.w > rect.h, sums, offset, value);
+        (mid, rect, lrect, rrect, left)
+    };
+    BinaryNode::Split {
+        rect,
+        left: Box::new(_binary_tree(lrect, &sums[..mid], index, offset, left)),
+        right: Box::new(_binary_tree(
+            rrect,
+            &sums[mid..],
+            index + mid,
+            sums[mid - 1],
+            value - left,
+        )),
+    }
+}
+
+/// Build the tree of regions [`binary`] splits `rect` into.
+///
+/// - `f_item_size` provide the size of an item
+///
+/// Leaves hold the item index and its distributed Rect, the same as [`binary`].
+///
+/// Panics if `items` is empty.
+///
+/// __Complexity__: `O(3⨯items.len()⨯log_2(items.len()))`
+pub fn binary_tree<N, T, S>(rect: Rect<N>, items: &[T], f_item_size: S) -> BinaryNode<N>
+where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+{
+    assert!(!items.is_empty(), "items must not be empty");
+    let mut size_total = N::zero();
+    let sums: Vec<N> = items
+        .iter()
+        .map(|item| {
+            size_total += f_item_size(item);
+            size_total
+        })
+        .collect();
+    let rect =
+        if size_total.is_zero() { Rect { w: N::zero(), h: N::zero(), ..rect } } else { rect };
+    _binary_tree(rect, &sums, 0, N::zero(), size_total)
+}
+
+/// Distribute `items` inside `rect` like [`squarify`], with between `min_per_strip`
+/// and `max_per_strip` items in each strip.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// A strip is broken at `max_per_strip` items and not before `min_per_strip` items,
+/// even if the aspect ratio gets worse. `(1, usize::MAX)` matches [`squarify`].
+///
+/// Panics if `max_per_strip` is zero or lower than `min_per_strip`.
+///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_constrained<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    min_per_strip: usize,
+    max_per_strip: usize,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    assert!(max_per_strip > 0, "max_per_strip must not be zero");
+    assert!(min_per_strip <= max_per_strip, "min_per_strip must not exceed max_per_strip");
+    if distribute_zero_area(rect, items, &mut f_item_set_rect)
+        || distribute_single(rect, items, &f_item_size, &mut f_item_set_rect)
+    {
+        return;
+    }
+    let scale = scale(rect, items, &f_item_size);
+    let f_item_size = |item: &T| f_item_size(item) * scale;
+    let options = SquarifyOptions::new();
+    let mut rect = rect;
+    let mut items = items;
+    while !items.is_empty() {
+        let is_wide = rect.w > rect.h;
+        let side = if is_wide { rect.h } else { rect.w };
+        let (mut split_idx, mut size_strip) = squarify_strip(side, items, &f_item_size);
+        let split_idx_constrained =
+            split_idx.clamp(min_per_strip.min(items.len()), max_per_strip.min(items.len()));
+        if split_idx_constrained != split_idx {
+            split_idx = split_idx_constrained;
+            size_strip = items[..split_idx].iter().map(&f_item_size).sum();
+        }
+        items = squarify_place_strip(
+            &mut rect,
+            is_wide,
+            items,
+            (split_idx, size_strip),
+            &options,
+            &f_item_size,
+            &mut f_item_set_rect,
+        );
+    }
+}
+
+/// Compute the order to draw the tiles of `items` back to front, for pseudo-3D
+/// extruded tree maps.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// `camera_dir` is the direction the camera looks at the tiles from: tiles are
+/// sorted by increasing projection of their center onto `camera_dir`, so the
+/// farthest tiles come first. Ties keep the input order.
+///
+/// Returns item indices.
+///
+/// __Complexity__: `O(items.len()⨯log_2(items.len()))`
+pub fn painters_order<N, T, F>(items: &[T], f_get_rect: F, camera_dir: (N, N)) -> Vec<usize>
+where
+    N: NumOps + PartialOrd + One + Copy,
+    F: Fn(&T) -> Rect<N>,
+{
+    let two = N::one() + N::one();
+    let depths: Vec<N> = items
+        .iter()
+        .map(|item| {
+            let r = f_get_rect(item);
+            (r.x + r.w / two) * camera_dir.0 + (r.y + r.h / two) * camera_dir.1
+        })
+        .collect();
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by(|&a, &b| depths[a].partial_cmp(&depths[b]).unwrap_or(Ordering::Equal));
+    order
+}
+
+/// Distribute `items` inside `rect` with [`squarify`], culling the tiles too small to
+/// be seen at `zoom`.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// A tile is culled if its on-screen area (`area⨯zoom²`) is below `min_pixel_area`:
+/// its size is absorbed by the largest item and it gets a zero-area rect at the
+/// origin of the largest item tile. The largest item is never culled.
+///
+/// __Complexity__: `O(5⨯items.len())`
+pub fn lod_squarify<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    zoom: N,
+    min_pixel_area: N,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    if items.is_empty() || distribute_zero_area(rect, items, &mut f_item_set_rect) {
+        return;
+    }
+    let scale = scale(rect, items, &f_item_size);
+    let sizes: Vec<N> = items.iter().map(&f_item_size).collect();
+    let largest = (1..sizes.len()).fold(0, |b, idx| if sizes[idx] > sizes[b] { idx } else { b });
+
+    let rect_zero = Rect::from_size(N::zero(), N::zero());
+    let mut tiles: Vec<(usize, N, Rect<N>)> = Vec::with_capacity(sizes.len());
+    let mut size_culled = N::zero();
+    for (idx, &size) in sizes.iter().enumerate() {
+        if idx != largest && size * scale * zoom * zoom < min_pixel_area {
+            size_culled += size;
+        } else {
+            tiles.push((idx, size, rect_zero));
+        }
+    }
+    if let Some(tile) = tiles.iter_mut().find(|tile| tile.0 == largest) {
+        tile.1 += size_culled;
+    }
+    squarify(rect, &mut tiles, |tile| tile.1, |tile, r| tile.2 = r);
+
+    let rect_largest = tiles.iter().find(|tile| tile.0 == largest).unwrap().2;
+    let rect_culled = Rect { w: N::zero(), h: N::zero(), ..rect_largest };
+    let mut tiles = tiles.into_iter().peekable();
+    for (idx, item) in items.iter_mut().enumerate() {
+        match tiles.next_if(|tile| tile.0 == idx) {
+            Some((_, _, r)) => f_item_set_rect(item, r),
+            None => f_item_set_rect(item, rect_culled),
+        }
+    }
+}
+
+/// Inset every tile by a padding growing with its aspect ratio, so thin tiles get
+/// more room around their labels.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+/// - `f_set_rect` receive the padded Rect of an item.
+///   Called once for each item and in a stable order.
+///
+/// The padding is `base⨯aspect_ratio`, capped at `cap` and at half the shortest side
+/// of the tile so it never gets a negative size. Tiles without area are unchanged.
+///
+/// __Complexity__: `O(items.len())`
+pub fn pad_by_aspect<N, T, F, G>(items: &mut [T], f_get_rect: F, mut f_set_rect: G, base: N, cap: N)
+where
+    N: NumOps + PartialOrd + Zero + One + Copy,
+    F: Fn(&T) -> Rect<N>,
+    G: FnMut(&mut T, Rect<N>),
+{
+    let two = N::one() + N::one();
+    for item in items {
+        let r = f_get_rect(item);
+        if (r.w * r.h).is_zero() {
+            f_set_rect(item, r);
+            continue;
+        }
+        let mut padding = base * r.aspect_ratio();
+        if padding > cap {
+            padding = cap;
+        }
+        let half_side = if r.w < r.h { r.w } else { r.h } / two;
+        if padding > half_side {
+            padding = half_side;
+        }
+        f_set_rect(item, r.inset(padding, padding, padding, padding));
+    }
+}
+
+/// Distribute `items` inside `rect` with [`squarify`], reserving `gutter_budget` of
+/// the container area to the gutters between tiles.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Each tile is shrunk around its center by `sqrt(1 - gutter_budget)` on both sides,
+/// so gaps are proportional to tile dimensions and the tiles cover exactly
+/// `1 - gutter_budget` of the container, whatever its size.
+///
+/// Panics if `gutter_budget` is not between 0 and 1.
+///
+/// __Complexity__: `O(3⨯items.len())`
+pub fn squarify_with_gutter_budget<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    gutter_budget: N,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: Float + NumAssignOps + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    assert!(
+        gutter_budget >= N::zero() && gutter_budget <= N::one(),
+        "gutter_budget must be between 0 and 1"
+    );
+    let k = (N::one() - gutter_budget).sqrt();
+    let two = N::one() + N::one();
+    squarify(rect, items, f_item_size, |item, r| {
+        let (w, h) = (r.w * k, r.h * k);
+        f_item_set_rect(item, Rect { x: r.x + (r.w - w) / two, y: r.y + (r.h - h) / two, w, h })
+    });
+}
+
+/// Clamp the corner `radius` of each tile of `items` so rounded corners never overlap.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// Returns the corner radius of each item, at most [`Rect::max_corner_radius`].
+///
+/// __Complexity__: `O(items.len())`
+pub fn corner_radii<N, T, F>(items: &[T], f_get_rect: F, radius: N) -> Vec<N>
+where
+    N: NumOps + PartialOrd + One + Copy,
+    F: Fn(&T) -> Rect<N>,
+{
+    items
+        .iter()
+        .map(|item| {
+            let max_radius = f_get_rect(item).max_corner_radius();
+            if radius < max_radius {
+                radius
+            } else {
+                max_radius
+            }
+        })
+        .collect()
+}
+
+/// Distribute `items` inside `rect` like [`binary`] after the size of the item at
+/// `changed_index` changed.
+///
+/// - `old_layout` the tiles of [`binary`] inside `rect` before the change, one per item
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// This is a full [`binary`] relayout, not an incremental one: changing a size
+/// changes the total size, which moves the root split and so every tile, even in
+/// subtrees without the changed item. `old_layout` is only checked against `items`.
+/// The same goes for [`squarify`] and the other algorithms.
+///
+/// Panics if `old_layout` doesn't have as many rects as `items` or if
+/// `changed_index` is out of bounds.
+///
+/// __Complexity__: `O(3⨯items.len()⨯log_2(items.len()))`
+pub fn update_one<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    changed_index: usize,
+    old_layout: &[Rect<N>],
+    f_item_size: S,
+    f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    assert_eq!(old_layout.len(), items.len(), "old_layout must have a rect per item");
+    assert!(changed_index < items.len(), "changed_index out of bounds");
+    binary(rect, items, f_item_size, f_item_set_rect);
+}
+
+/// Distribute `items` inside `rect` along a timeline: rows of `row_height` filled
+/// left to right in input order, wrapping to the next row when a row is full.
+///
+/// - `f_duration` provide the duration of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Tile areas are proportional to durations, scaled so the tiles would cover `rect`.
+/// A row is full when the middle of its next item would be past the right of `rect`,
+/// then each row is stretched to the width of `rect`. Rows are not scaled vertically,
+/// so the last one can end above or past the bottom of `rect`.
+///
+/// If `rect` has no area or the durations add up to zero, items get zero-area rects
+/// at the origin of `rect`.
+///
+/// Panics if `row_height` is not positive.
+///
+/// __Complexity__: `O(2⨯items.len())`
+pub fn timeline<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    row_height: N,
+    f_duration: S,
+    mut f_item_set_rect: R,
+) where
+    N: Float,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    assert!(row_height > N::zero(), "row_height must be positive");
+    let durations: Vec<N> = items.iter().map(&f_duration).collect();
+    let duration_total = durations.iter().fold(N::zero(), |a, &b| a + b);
+    let fill = if duration_total > N::zero() { rect } else { Rect { w: N::zero(), ..rect } };
+    if distribute_zero_area(fill, items, &mut f_item_set_rect) {
+        return;
+    }
+    let scale = rect.w * rect.h / (row_height * duration_total);
+
+    // (items count, duration total) of each row
+    let two = N::one() + N::one();
+    let mut rows: Vec<(usize, N)> = Vec::new();
+    let mut pos = N::zero();
+    for &duration in &durations {
+        let w = duration * scale;
+        match rows.last_mut() {
+            Some((len, row_duration)) if duration.is_zero() || pos + w / two <= rect.w => {
+                *len += 1;
+                *row_duration = *row_duration + duration;
+                pos = pos + w;
+            }
+            _ => {
+                rows.push((1, duration));
+                pos = w;
+            }
+        }
+    }
+
+    let mut idx = 0;
+    let mut y = rect.y;
+    for (len, row_duration) in rows {
+        let mut x = rect.x;
+        for (item, &duration) in items[idx..idx + len].iter_mut().zip(&durations[idx..]) {
+            let w =
+                if row_duration.is_zero() { N::zero() } else { duration / row_duration * rect.w };
+            f_item_set_rect(item, Rect { x, y, w, h: row_height });
+            x = x + w;
+        }
+        idx += len;
+        y = y + row_height;
+    }
+}
+
+/// Compute the container area at which the tile of the smallest item of `items`
+/// would be `min_tile_area`.
+///
+/// - `f_item_size` provide the size of an item
+///
+/// Below that area some tiles are too small to read, so a UI can switch to a list
+/// view. Zero-sized items are ignored, returns zero if no item has a size.
+///
+/// __Complexity__: `O(items.len())`
+pub fn min_container_area<N, T, S>(items: &[T], f_item_size: S, min_tile_area: N) -> N
+where
+    N: NumOps + PartialOrd + Zero + Copy,
+    S: Fn(&T) -> N,
+{
+    let mut size_total = N::zero();
+    let mut size_min: Option<N> = None;
+    for size in items.iter().map(f_item_size).filter(|size| !size.is_zero()) {
+        size_total = size_total + size;
+        size_min = Some(match size_min {
+            Some(size_min) if size_min < size => size_min,
+            _ => size,
+        });
+    }
+    match size_min {
+        Some(size_min) => min_tile_area * size_total / size_min,
+        None => N::zero(),
+    }
+}
+
+/// Distribute `items` inside `rect` in `n_cols` columns of the same width, each
+/// column stacking its items vertically with [`slice`].
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Items are split in consecutive ranges of total size as close as possible to
+/// `1 / n_cols` of the total, so tile heights are proportional to item sizes within
+/// their column, like a column chart.
+///
+/// Panics if `n_cols` is zero.
+///
+/// __Complexity__: `O(4⨯items.len() + n_cols)`
+pub fn columns<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    n_cols: usize,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum + NumCast,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    assert!(n_cols > 0, "n_cols must not be zero");
+    let sums = prefix_sums(items, &f_item_size);
+    let size_total = sums[items.len()];
+    let n = N::from(n_cols).unwrap();
+    let col_width = rect.w / n;
+    let mut col_rect = Rect { w: col_width, ..rect };
+    let mut idx0 = 0;
+    for col in 1..=n_cols {
+        let idx1 = if col == n_cols {
+            items.len()
+        } else {
+            closest_prefix_sum(&sums, idx0, size_total * N::from(col).unwrap() / n)
+        };
+        slice(col_rect, &mut items[idx0..idx1], &f_item_size, &mut f_item_set_rect);
+        col_rect.x += col_width;
+        idx0 = idx1;
+    }
+}
+
+/// Compute the adjacency graph of the tiles of `items`: the tiles sharing an edge
+/// segment longer than `epsilon` with each tile.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// Edges closer than `epsilon` are considered shared, tiles only touching by a
+/// corner aren't adjacent. Returns the sorted indices of adjacent tiles, per tile.
+///
+/// __Complexity__: `O(items.len()^2)`
+pub fn adjacency<N, T, F>(items: &[T], f_get_rect: F, epsilon: N) -> Vec<Vec<usize>>
+where
+    N: NumOps + PartialOrd + Copy,
+    F: Fn(&T) -> Rect<N>,
+{
+    let rects: Vec<Rect<N>> = items.iter().map(f_get_rect).collect();
+    let close = |a: N, b: N| if a > b { a - b <= epsilon } else { b - a <= epsilon };
+    let overlap = |a0: N, a1: N, b0: N, b1: N| {
+        let lo = if a0 > b0 { a0 } else { b0 };
+        let hi = if a1 < b1 { a1 } else { b1 };
+        hi > lo && hi - lo > epsilon
+    };
+    let mut graph = vec![Vec::new(); rects.len()];
+    for (i, a) in rects.iter().enumerate() {
+        for (j, b) in rects.iter().enumerate().skip(i + 1) {
+            let side_by_side = (close(a.max_x(), b.min_x()) || close(b.max_x(), a.min_x()))
+                && overlap(a.min_y(), a.max_y(), b.min_y(), b.max_y());
+            let stacked = (close(a.max_y(), b.min_y()) || close(b.max_y(), a.min_y()))
+                && overlap(a.min_x(), a.max_x(), b.min_x(), b.max_x());
+            if side_by_side || stacked {
+                graph[i].push(j);
+                graph[j].push(i);
+            }
+        }
+    }
+    graph
+}
+
+/// Order in which tiles are revealed by entrance animations, see [`reveal_order`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RevealMode {
+    /// Largest tiles first
+    LargestFirst,
+    /// Top to bottom, then left to right, by tile origin
+    Reading,
+    /// Closest tiles to the center of the layout first
+    SpiralOut,
+}
+
+/// Compute the order in which to reveal the tiles of `items` one by one.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+///
+/// Ties keep the input order. Returns item indices.
+///
+/// __Complexity__: `O(items.len()⨯log_2(items.len()))`
+pub fn reveal_order<N, T, F>(items: &[T], f_get_rect: F, mode: RevealMode) -> Vec<usize>
+where
+    N: NumOps + PartialOrd + One + Copy,
+    F: Fn(&T) -> Rect<N>,
+{
+    let rects: Vec<Rect<N>> = items.iter().map(f_get_rect).collect();
+    let cmp = |a: N, b: N| a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+    let mut order: Vec<usize> = (0..rects.len()).collect();
+    match mode {
+        RevealMode::LargestFirst => {
+            order.sort_by(|&a, &b| cmp(rects[b].w * rects[b].h, rects[a].w * rects[a].h))
+        }
+        RevealMode::Reading => order.sort_by(|&a, &b| {
+            cmp(rects[a].y, rects[b].y).then_with(|| cmp(rects[a].x, rects[b].x))
+        }),
+        RevealMode::SpiralOut => {
+            if let Some(bounds) = rects.iter().copied().reduce(|bounds, r| bounds.union(&r)) {
+                let two = N::one() + N::one();
+                let center = |r: &Rect<N>| (r.x + r.w / two, r.y + r.h / two);
+                let (cx, cy) = center(&bounds);
+                let distances: Vec<N> = rects
+                    .iter()
+                    .map(|r| {
+                        let (x, y) = center(r);
+                        (x - cx) * (x - cx) + (y - cy) * (y - cy)
+                    })
+                    .collect();
+                order.sort_by(|&a, &b| cmp(distances[a], distances[b]));
+            }
+        }
+    }
+    order
+}
+
+/// Distribute `items` inside `rect` like [`squarify`], but choosing strip breaks
+/// that minimize the variance of the tiles aspect ratio instead of their mean.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Tiles end up equally non-square rather than a mix of perfect and bad ones.
+/// Every possible length of each strip is evaluated against the already placed
+/// tiles, and each length computes the aspect ratio of all the tiles of the strip,
+/// which costs a cubic number of aspect ratio computations. Strips are
+/// chosen greedily, so the final variance isn't guaranteed to be lower than
+/// [`squarify`] one. As a lone tile has no variance, the first strip only grows
+/// past one item over tiles of the same aspect ratio, ties being broken by the
+/// mean aspect ratio. Zero-area tiles have no aspect ratio and are left out of
+/// the variance.
+///
+/// A single item fills `rect` exactly, unless its size is zero.
+///
+/// __Complexity__: `O(items.len()^3)`
+pub fn squarify_min_variance<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: Float + NumAssignOps + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    if distribute_zero_area(rect, items, &mut f_item_set_rect)
+        || distribute_single(rect, items, &f_item_size, &mut f_item_set_rect)
+    {
+        return;
+    }
+    let scale = scale(rect, items, &f_item_size);
+    let f_item_size = |item: &T| f_item_size(item) * scale;
+    let options = SquarifyOptions::new();
+    // Count, sum and sum of squares of the placed tiles aspect ratio
+    let (mut count, mut sum, mut sum_sq) = (N::zero(), N::zero(), N::zero());
+    let mut rect = rect;
+    let mut items = items;
+    while !items.is_empty() {
+        let is_wide = rect.w > rect.h;
+        let side = if is_wide { rect.h } else { rect.w };
+
+        // (len, size, (variance, mean), (count, sum, sum_sq))
+        let zero = N::zero();
+        let mut best = (1, zero, (N::infinity(), N::infinity()), (zero, zero, zero));
+        let mut size_strip = N::zero();
+        for len in 1..=items.len() {
+            size_strip += f_item_size(&items[len - 1]);
+            let thickness = size_strip / side;
+            let (mut strip_count, mut strip_sum, mut strip_sum_sq) = (zero, zero, zero);
+            for item in &items[..len] {
+                let length = f_item_size(item) / thickness;
+                let ratio =
+                    if length > thickness { length / thickness } else { thickness / length };
+                if ratio.is_finite() {
+                    strip_count += N::one();
+                    strip_sum += ratio;
+                    strip_sum_sq += ratio * ratio;
+                }
+            }
+            let n = count + strip_count;
+            let score = if n.is_zero() {
+                (zero, zero)
+            } else {
+                let mean = (sum + strip_sum) / n;
+                ((sum_sq + strip_sum_sq) / n - mean * mean, mean)
+            };
+            if len == 1 || score < best.2 {
+                best = (len, size_strip, score, (strip_count, strip_sum, strip_sum_sq));
+            }
+        }
+
+        let (len, size_strip, _, (strip_count, strip_sum, strip_sum_sq)) = best;
+        count += strip_count;
+        sum += strip_sum;
+        sum_sq += strip_sum_sq;
+        items = squarify_place_strip(
+            &mut rect,
+            is_wide,
+            items,
+            (len, size_strip),
+            &options,
+            &f_item_size,
+            &mut f_item_set_rect,
+        );
+    }
+}
+
+/// Distribute `items` inside `rect` with [`squarify`], avoiding a `notch` cut out of
+/// its top edge, like the camera cutout of a phone screen.
+///
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// The area below the notch and the areas on its left and right are filled, see
+/// [`squarify_avoiding`].
+///
+/// __Complexity__: `O(4⨯items.len())`
+pub fn squarify_notched<N, T, S, R>(
+    rect: Rect<N>,
+    notch: Rect<N>,
+    items: &mut [T],
+    f_item_size: S,
+    f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    squarify_avoiding(rect, items, &[notch], f_item_size, f_item_set_rect);
+}
+
+/// Push every tile of `items` away from `centroid`, for exploded views.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+/// - `f_set_rect` receive the translated Rect of an item.
+///   Called once for each item and in a stable order.
+///
+/// Each tile is translated by `factor` times the vector from `centroid` to its
+/// center, without resizing, so farther tiles move more. See [`weighted_centroid`]
+/// for the centroid of a layout.
+///
+/// __Complexity__: `O(items.len())`
+pub fn explode_layout<N, T, F, G>(
+    items: &mut [T],
+    f_get_rect: F,
+    mut f_set_rect: G,
+    factor: N,
+    centroid: (N, N),
+) where
+    N: NumOps + One + Copy,
+    F: Fn(&T) -> Rect<N>,
+    G: FnMut(&mut T, Rect<N>),
+{
+    let two = N::one() + N::one();
+    for item in items {
+        let r = f_get_rect(item);
+        let (dx, dy) = (r.x + r.w / two - centroid.0, r.y + r.h / two - centroid.1);
+        f_set_rect(item, r.translate(dx * factor, dy * factor));
+    }
+}
+
+/// Inset the tiles of `items` so tiles of different categories are separated by
+/// `gap`, while adjacent tiles of the same category still abut.
+///
+/// - `f_get_rect` provide the distributed Rect of an item
+/// - `f_set_rect` receive the padded Rect of an item.
+///   Called once for each item and in a stable order.
+/// - `f_category` provide the category of an item
+///
+/// Adjacent tiles are found with [`adjacency`] and `epsilon`. Each side of a tile
+/// shared with a tile of another category is inset by half `gap`.
+///
+/// __Complexity__: `O(items.len()^2)`
+pub fn pad_between_categories<N, T, K, F, G, C>(
+    items: &mut [T],
+    f_get_rect: F,
+    mut f_set_rect: G,
+    f_category: C,
+    gap: N,
+    epsilon: N,
+) where
+    N: NumOps + PartialOrd + Zero + One + Copy,
+    K: PartialEq,
+    F: Fn(&T) -> Rect<N>,
+    G: FnMut(&mut T, Rect<N>),
+    C: Fn(&T) -> K,
+{
+    let graph = adjacency(items, &f_get_rect, epsilon);
+    let close = |a: N, b: N| if a > b { a - b <= epsilon } else { b - a <= epsilon };
+    let half_gap = gap / (N::one() + N::one());
+    let insets: Vec<(N, N, N, N)> = graph
+        .iter()
+        .enumerate()
+        .map(|(i, neighbors)| {
+            let (a, category) = (f_get_rect(&items[i]), f_category(&items[i]));
+            let (mut top, mut right, mut bottom, mut left) =
+                (N::zero(), N::zero(), N::zero(), N::zero());
+            for &j in neighbors.iter().filter(|&&j| f_category(&items[j]) != category) {
+                let b = f_get_rect(&items[j]);
+                if close(a.max_x(), b.min_x()) {
+                    right = half_gap;
+                } else if close(b.max_x(), a.min_x()) {
+                    left = half_gap;
+                } else if close(a.max_y(), b.min_y()) {
+                    bottom = half_gap;
+                } else {
+                    top = half_gap;
+                }
+            }
+            (top, right, bottom, left)
+        })
+        .collect();
+    for (item, (top, right, bottom, left)) in items.iter_mut().zip(insets) {
+        let r = f_get_rect(item).inset(top, right, bottom, left);
+        f_set_rect(item, r);
+    }
+}
+
+/// Distribute `items` inside `rect` with `algorithm`, breaking its ties towards the
+/// `previous` layout for minimal reflow between frames.
+///
+/// - `previous` is the previous layout, `previous[i]` being the tile of `items[i]`.
+///   Items without a previous tile don't constrain the layout.
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// Wherever `algorithm` has equally good choices, the one whose areas are the closest
+/// to the bounding box of the `previous` tiles of their items is kept:
+/// - [`Algorithm::Binary`] splits square areas side by side or stacked,
+/// - [`Algorithm::Squarify`] lays strips along either side of square areas, and ends
+///   a strip before its last item if that item doesn't make the strip aspect ratio
+///   better, each tile of the strip being compared to its own previous tile,
+/// - [`Algorithm::OrderedPivotByMiddle`] and [`Algorithm::OrderedPivotBySize`] split
+///   square areas either way, pick among the 2 middle items of an even count or the
+///   items of the same largest size, and among the items next to the pivot giving it
+///   the same aspect ratio,
+/// - [`Algorithm::Slice`] and [`Algorithm::Dice`] have no choice.
+///
+/// Ties are broken one after the other, from the outermost split to the innermost.
+///
+/// __Complexity__: `O(algorithm complexity + ties⨯items.len())`
+pub fn reflow<N, T, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    previous: &[Rect<N>],
+    algorithm: Algorithm,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: Float + NumAssignOps + Sum,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let rect_zero = Rect::from_size(N::zero(), N::zero());
+    let mut tiles: Vec<(usize, N, Rect<N>)> =
+        items.iter().enumerate().map(|(idx, item)| (idx, f_item_size(item), rect_zero)).collect();
+    let f_cost = |group: &[(usize, N, Rect<N>)], r: Rect<N>| {
+        let start = group.first().map_or(0, |&(idx, _, _)| idx).min(previous.len());
+        let end = (start + group.len()).min(previous.len());
+        previous[start..end]
+            .iter()
+            .fold(None, |bounds: Option<Rect<N>>, p| Some(bounds.map_or(*p, |b| b.union(p))))
+            .map_or(N::zero(), |bounds| displacement(&bounds, &r))
+    };
+    let f_tile_size = |&(_, size, _): &(usize, N, Rect<N>)| size;
+    let f_tile_set_rect = |(_, _, tile_r): &mut (usize, N, Rect<N>), r| *tile_r = r;
+    algorithm._layout(rect, &mut tiles, f_tile_size, f_tile_set_rect, Some(&f_cost));
+    for (item, (_, _, r)) in items.iter_mut().zip(tiles) {
+        f_item_set_rect(item, r);
+    }
+}
+
+/// Distribute `items` inside `rect` as a flame graph, in horizontal layers by depth.
+///
+/// - `f_depth` provide the depth of an item, 0 for the roots
+/// - `f_item_size` provide the size of an item
+/// - `f_item_set_rect` receive the item distributed Rect.
+///   Called once for each item and in a stable order.
+///
+/// `items` is a tree in depth-first order: the parent of an item is the last item
+/// before it one level up. Layers have the same height, roots at the top. Tile
+/// widths are proportional to sizes, the roots spanning the width of `rect`, and
+/// children are placed left to right from their parent left edge, so they sit
+/// below it.
+///
+/// Panics if an item is more than one level deeper than the item before it.
+///
+/// __Complexity__: `O(2⨯items.len())`
+pub fn flame<N, T, D, S, R>(
+    rect: Rect<N>,
+    items: &mut [T],
+    f_depth: D,
+    f_item_size: S,
+    mut f_item_set_rect: R,
+) where
+    N: NumAssignOps + NumOps + PartialOrd + Zero + One + Copy + NumCast,
+    D: Fn(&T) -> usize,
+    S: Fn(&T) -> N,
+    R: FnMut(&mut T, Rect<N>),
+{
+    let mut depth_max = 0;
+    let mut size_roots = N::zero();
+    for item in items.iter() {
+        let depth = f_depth(item);
+        depth_max = depth_max.max(depth);
+        if depth == 0 {
+            size_roots += f_item_size(item);
+        }
+    }
+    if items.is_empty() || size_roots.is_zero() {
+        distribute_zero_area(Rect { w: N::zero(), ..rect }, items, &mut f_item_set_rect);
+        return;
+    }
+    let scale = rect.w / size_roots;
+    let layer_height = rect.h / N::from(depth_max + 1).unwrap();
+
+    // Left edge of the next tile of each layer
+    let mut cursors = vec![rect.x];
+    for item in items.iter_mut() {
+        let depth = f_depth(item);
+        assert!(depth < cursors.len(), "item depth must not exceed its parent depth + 1");
+        let x = cursors[depth];
+        let w = f_item_size(item) * scale;
+        let y = rect.y + N::from(depth).unwrap() * layer_height;
+        f_item_set_rect(item, Rect { x, y, w, h: layer_height });
+        cursors.truncate(depth + 1);
+        cursors[depth] += w;
+        cursors.push(x);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{Debug, Display};
+    use std::ops::Mul;
+
+    use num_traits::Signed;
+
+    use super::*;
+
+    fn svg<N: NumOps + Copy + Display>(
+        view_box: Rect<N>,
+        slice: &[(usize, N, Rect<N>)],
+        scale: N,
+    ) -> String {
+        use std::fmt::Write;
+
         let mut f = String::new();
         writeln!(
             &mut f,
@@ -560,255 +5576,2746 @@ mod tests {
             view_box.h * scale
         )
         .unwrap();
-        for (_i, _size, r) in slice {
-            writeln!(
-                &mut f,
-                r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="url(#g)" />"#,
-                r.x, r.y, r.w, r.h
-            )
-            .unwrap();
+        for (_i, _size, r) in slice {
+            writeln!(
+                &mut f,
+                r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="url(#g)" />"#,
+                r.x, r.y, r.w, r.h
+            )
+            .unwrap();
+        }
+        writeln!(&mut f, "</svg>").unwrap();
+
+        f
+    }
+
+    fn mkslice<N: Copy + Zero>(slice: &[N]) -> Vec<(usize, N, Rect<N>)> {
+        slice
+            .iter()
+            .copied()
+            .enumerate()
+            .map(|(i, n)| (i, n, Rect::from_size(N::zero(), N::zero())))
+            .collect()
+    }
+
+    const EPSILON: f32 = 0.000001;
+    fn mkset_rect11<N>(epsilon: N) -> impl FnMut(&mut (usize, N, Rect<N>), Rect<N>)
+    where
+        N: NumOps + Signed + PartialOrd + Copy + Debug,
+    {
+        let mut idx = 0;
+        move |(i, n, item_r), r| {
+            assert_eq!(*i, idx, "f_item_set_rect must be called in stable order");
+            let d = (r.w * r.h - *n).abs();
+            assert!(
+                d <= epsilon,
+                "item rect size must match size, n = {:?}, r = {:?}, d = {:?}",
+                *n,
+                r,
+                d,
+            );
+            *item_r = r;
+            idx += 1;
+        }
+    }
+
+    fn mkset_rect<N: Mul<Output = N> + PartialOrd + Copy + Debug>(
+    ) -> impl FnMut(&mut (usize, N, Rect<N>), Rect<N>) {
+        let mut idx = 0;
+        move |(i, _n, item_r), r| {
+            assert_eq!(*i, idx, "f_item_set_rect must be called in stable order");
+            *item_r = r;
+            idx += 1;
+        }
+    }
+
+    /// Checks that the tiles lie in `rect`, overlap neither each other nor `holes`, and cover `area`
+    fn assert_tiling(
+        rect: Rect<f32>,
+        items: &[(usize, f32, Rect<f32>)],
+        holes: &[Rect<f32>],
+        area: f32,
+    ) {
+        let mut total = 0.;
+        for (idx, &(i, _, r)) in items.iter().enumerate() {
+            assert!(rect.contains_rect(&r), "{} {:?} is outside {:?}", i, r, rect);
+            for hole in holes {
+                assert!(r.intersection(hole).is_none(), "{} {:?} overlaps {:?}", i, r, hole);
+            }
+            for &(j, _, other) in &items[idx + 1..] {
+                assert!(
+                    r.intersection(&other).is_none(),
+                    "{} {:?} overlaps {} {:?}",
+                    i,
+                    r,
+                    j,
+                    other
+                );
+            }
+            total += r.w * r.h;
+        }
+        assert!((total - area).abs() <= EPSILON * area, "covered area {} != {}", total, area);
+    }
+
+    #[test]
+    fn binary_f32() {
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        binary(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut slice[..],
+            |&(_, n, _)| n,
+            mkset_rect11(EPSILON),
+        );
+        assert_eq!(
+            slice,
+            [
+                (0, 6.0, Rect { x: 0.0, y: 0.0, w: 3.0, h: 2.0 }),
+                (1, 6.0, Rect { x: 0.0, y: 2.0, w: 3.0, h: 2.0 }),
+                (2, 4.0, Rect { x: 3.0, y: 0.0, w: 3.0, h: 1.3333334 }),
+                (3, 3.0, Rect { x: 3.0, y: 1.3333334, w: 1.125, h: 2.6666665 }),
+                (4, 2.0, Rect { x: 4.125, y: 1.3333334, w: 1.875, h: 1.0666667 }),
+                (5, 2.0, Rect { x: 4.125, y: 2.4, w: 1.25, h: 1.5999999 }),
+                (6, 1.0, Rect { x: 5.375, y: 2.4, w: 0.625, h: 1.5999999 })
+            ]
+        );
+        eprintln!(
+            "<!-- binary -->\n{}",
+            svg(Rect { x: 0., y: 0., w: 6., h: 4. }, &slice[..], 50.0)
+        );
+
+        let mut slice = mkslice::<f32>(&[12., 12., 8., 6., 4., 4., 2.]);
+        binary(Rect { x: 0., y: 0., w: 6., h: 4. }, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        assert_eq!(
+            slice,
+            [
+                (0, 12.0, Rect { x: 0.0, y: 0.0, w: 3.0, h: 2.0 }),
+                (1, 12.0, Rect { x: 0.0, y: 2.0, w: 3.0, h: 2.0 }),
+                (2, 8.0, Rect { x: 3.0, y: 0.0, w: 3.0, h: 1.3333334 }),
+                (3, 6.0, Rect { x: 3.0, y: 1.3333334, w: 1.125, h: 2.6666665 }),
+                (4, 4.0, Rect { x: 4.125, y: 1.3333334, w: 1.875, h: 1.0666667 }),
+                (5, 4.0, Rect { x: 4.125, y: 2.4, w: 1.25, h: 1.5999999 }),
+                (6, 2.0, Rect { x: 5.375, y: 2.4, w: 0.625, h: 1.5999999 })
+            ]
+        );
+    }
+
+    #[test]
+    fn squarify_paper_example_f32() {
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        _squarify(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut slice[..],
+            &SquarifyOptions::new(),
+            |&(_, n, _)| n,
+            mkset_rect11(EPSILON),
+            None,
+        );
+        assert_eq!(
+            slice,
+            [
+                (0, 6.0, Rect { x: 0.0, y: 0.0, w: 3.0, h: 2.0 }),
+                (1, 6.0, Rect { x: 0.0, y: 2.0, w: 3.0, h: 2.0 }),
+                (2, 4.0, Rect { x: 3.0, y: 0.0, w: 1.7142857, h: 2.3333333 }),
+                (3, 3.0, Rect { x: 4.714286, y: 0.0, w: 1.2857141, h: 2.3333333 }),
+                (4, 2.0, Rect { x: 3.0, y: 2.3333333, w: 1.1999999, h: 1.6666667 }),
+                (5, 2.0, Rect { x: 4.2, y: 2.3333333, w: 1.1999999, h: 1.6666667 }),
+                (6, 1.0, Rect { x: 5.3999996, y: 2.3333333, w: 0.60000014, h: 1.6666667 })
+            ]
+        );
+        eprintln!(
+            "<!-- squarify -->\n{}",
+            svg(Rect { x: 0., y: 0., w: 6., h: 4. }, &slice[..], 50.0)
+        );
+        squarify(
+            Rect { x: 0., y: 0., w: 12., h: 8. },
+            &mut slice[..],
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        assert_eq!(
+            slice,
+            [
+                (0, 6.0, Rect { x: 0.0, y: 0.0, w: 6.0, h: 4.0 }),
+                (1, 6.0, Rect { x: 0.0, y: 4.0, w: 6.0, h: 4.0 }),
+                (2, 4.0, Rect { x: 6.0, y: 0.0, w: 3.4285715, h: 4.6666665 }),
+                (3, 3.0, Rect { x: 9.428572, y: 0.0, w: 2.5714283, h: 4.6666665 }),
+                (4, 2.0, Rect { x: 6.0, y: 4.6666665, w: 2.3999999, h: 3.3333335 }),
+                (5, 2.0, Rect { x: 8.4, y: 4.6666665, w: 2.3999999, h: 3.3333335 }),
+                (6, 1.0, Rect { x: 10.799999, y: 4.6666665, w: 1.2000003, h: 3.3333335 })
+            ]
+        );
+        squarify(
+            Rect { x: 1., y: 2., w: 12., h: 8. },
+            &mut slice[..],
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        assert_eq!(
+            slice,
+            [
+                (0, 6.0, Rect { x: 1.0, y: 2.0, w: 6.0, h: 4.0 }),
+                (1, 6.0, Rect { x: 1.0, y: 6.0, w: 6.0, h: 4.0 }),
+                (2, 4.0, Rect { x: 7.0, y: 2.0, w: 3.4285715, h: 4.6666665 }),
+                (3, 3.0, Rect { x: 10.428572, y: 2.0, w: 2.5714283, h: 4.6666665 }),
+                (4, 2.0, Rect { x: 7.0, y: 6.6666665, w: 2.3999999, h: 3.3333335 }),
+                (5, 2.0, Rect { x: 9.4, y: 6.6666665, w: 2.3999999, h: 3.3333335 }),
+                (6, 1.0, Rect { x: 11.799999, y: 6.6666665, w: 1.2000003, h: 3.3333335 })
+            ]
+        );
+    }
+
+    #[test]
+    fn ordered_pivot_by_middle_f32() {
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        ordered_pivot_by_middle(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut slice[..],
+            |&(_, n, _)| n,
+            mkset_rect11(EPSILON),
+        );
+        eprintln!(
+            "<!-- ordered_pivot_by_middle -->\n{}",
+            svg(Rect { x: 0., y: 0., w: 6., h: 4. }, &slice[..], 50.0)
+        );
+        assert_eq!(
+            slice,
+            [
+                (0, 6.0, Rect { x: 0.0, y: 0.0, w: 1.5, h: 4.0 }),
+                (1, 6.0, Rect { x: 1.5, y: 0.0, w: 2.5, h: 2.4 }),
+                (2, 4.0, Rect { x: 1.5, y: 2.4, w: 2.5, h: 1.5999999 }),
+                (3, 3.0, Rect { x: 4.0, y: 0.0, w: 1.25, h: 2.4 }),
+                (4, 2.0, Rect { x: 4.0, y: 2.4, w: 1.25, h: 1.5999999 }),
+                (5, 2.0, Rect { x: 5.25, y: 0.0, w: 0.75, h: 2.6666667 }),
+                (6, 1.0, Rect { x: 5.25, y: 2.6666667, w: 0.75, h: 1.3333333 })
+            ]
+        );
+
+        let mut slice = mkslice::<f32>(&[12., 12., 8., 6., 4., 4., 2.]);
+        ordered_pivot_by_middle(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut slice[..],
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        assert_eq!(
+            slice,
+            [
+                (0, 12.0, Rect { x: 0.0, y: 0.0, w: 1.5, h: 4.0 }),
+                (1, 12.0, Rect { x: 1.5, y: 0.0, w: 2.5, h: 2.4 }),
+                (2, 8.0, Rect { x: 1.5, y: 2.4, w: 2.5, h: 1.5999999 }),
+                (3, 6.0, Rect { x: 4.0, y: 0.0, w: 1.25, h: 2.4 }),
+                (4, 4.0, Rect { x: 4.0, y: 2.4, w: 1.25, h: 1.5999999 }),
+                (5, 4.0, Rect { x: 5.25, y: 0.0, w: 0.75, h: 2.6666667 }),
+                (6, 2.0, Rect { x: 5.25, y: 2.6666667, w: 0.75, h: 1.3333333 })
+            ]
+        );
+    }
+
+    #[test]
+    fn ordered_pivot_by_size_f32() {
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        ordered_pivot_by_size(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut slice[..],
+            |&(_, n, _)| n,
+            mkset_rect11(EPSILON),
+        );
+        eprintln!(
+            "<!-- ordered_pivot_by_size -->\n{}",
+            svg(Rect { x: 0., y: 0., w: 6., h: 4. }, &slice[..], 50.0)
+        );
+        assert_eq!(
+            slice,
+            [
+                (0, 6.0, Rect { x: 0.0, y: 0.0, w: 3.0, h: 2.0 }),
+                (1, 6.0, Rect { x: 0.0, y: 2.0, w: 3.0, h: 2.0 }),
+                (2, 4.0, Rect { x: 3.0, y: 0.0, w: 1.7142857, h: 2.3333333 }),
+                (3, 3.0, Rect { x: 4.714286, y: 0.0, w: 1.2857143, h: 2.3333333 }),
+                (4, 2.0, Rect { x: 3.0, y: 2.3333333, w: 2.3999999, h: 0.8333334 }),
+                (5, 2.0, Rect { x: 3.0, y: 3.1666665, w: 2.3999999, h: 0.8333334 }),
+                (6, 1.0, Rect { x: 5.3999996, y: 2.3333333, w: 0.60000014, h: 1.6666667 })
+            ]
+        );
+
+        let mut slice = mkslice::<f32>(&[12., 12., 8., 6., 4., 4., 2.]);
+        ordered_pivot_by_size(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut slice[..],
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        assert_eq!(
+            slice,
+            [
+                (0, 12.0, Rect { x: 0.0, y: 0.0, w: 3.0, h: 2.0 }),
+                (1, 12.0, Rect { x: 0.0, y: 2.0, w: 3.0, h: 2.0 }),
+                (2, 8.0, Rect { x: 3.0, y: 0.0, w: 1.7142857, h: 2.3333333 }),
+                (3, 6.0, Rect { x: 4.714286, y: 0.0, w: 1.2857143, h: 2.3333333 }),
+                (4, 4.0, Rect { x: 3.0, y: 2.3333333, w: 2.3999999, h: 0.8333334 }),
+                (5, 4.0, Rect { x: 3.0, y: 3.1666665, w: 2.3999999, h: 0.8333334 }),
+                (6, 2.0, Rect { x: 5.3999996, y: 2.3333333, w: 0.60000014, h: 1.6666667 })
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_coverage_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        assert_eq!(validate_coverage(container, &slice[..], |&(_, _, r)| r, EPSILON * 10.), Ok(()));
+
+        slice[6].2.w = 0.;
+        let err =
+            validate_coverage(container, &slice[..], |&(_, _, r)| r, EPSILON * 10.).unwrap_err();
+        assert_eq!(err.expected, 24.);
+        assert!((err.actual - 23.).abs() <= EPSILON * 10., "{:?}", err);
+        assert!((err.discrepancy - 1.).abs() <= EPSILON * 10., "{:?}", err);
+    }
+
+    #[test]
+    fn squarify_strip_order_stable_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        let options = SquarifyOptions::new().strip_order_stable(true);
+        squarify_with(
+            container,
+            &mut slice[..],
+            &options,
+            |&(_, n, _)| n,
+            mkset_rect11(EPSILON * 10.),
+        );
+        validate_coverage(container, &slice[..], |&(_, _, r)| r, EPSILON * 10.).unwrap();
+
+        let mut reading_order = slice.clone();
+        reading_order.sort_by(|(_, _, a), (_, _, b)| {
+            (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap_or(Ordering::Equal)
+        });
+        let reading_order: Vec<usize> = reading_order.iter().map(|&(i, _, _)| i).collect();
+        assert_eq!(reading_order, [0, 1, 2, 3, 4, 5, 6]);
+
+        // without the option, the second item is below the first strip
+        squarify(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        assert!(slice[1].2.y > slice[2].2.y);
+    }
+
+    #[test]
+    fn rect_map_f64() {
+        let r = Rect { x: 0.5f64, y: 1.25, w: 6., h: 4. };
+        assert_eq!(r.map(|v| v as f32), Rect { x: 0.5f32, y: 1.25, w: 6., h: 4. });
+        assert_eq!(r.map(|v| (v * 2.) as i32), Rect { x: 1, y: 2, w: 12, h: 8 });
+    }
+
+    #[test]
+    fn snap_half_pixel_f32() {
+        let container = Rect { x: 0., y: 0., w: 61., h: 41. };
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        let before = slice.clone();
+        snap_half_pixel(&mut slice[..], |&(_, _, r)| r, mkset_rect());
+
+        for &(_, _, r) in &slice {
+            for v in [r.x, r.y, r.x + r.w, r.y + r.h] {
+                assert_eq!((v * 2.).fract(), 0., "{:?} is not on a half pixel", r);
+            }
+        }
+        for (a, b) in before.iter().zip(&slice) {
+            for (c, d) in before.iter().zip(&slice) {
+                if a.2.x + a.2.w == c.2.x {
+                    assert_eq!(b.2.x + b.2.w, d.2.x, "{:?} and {:?} are no more flush", b, d);
+                }
+                if a.2.y + a.2.h == c.2.y {
+                    assert_eq!(b.2.y + b.2.h, d.2.y, "{:?} and {:?} are no more flush", b, d);
+                }
+            }
+        }
+        validate_coverage(container, &slice[..], |&(_, _, r)| r, 0.).unwrap();
+    }
+
+    #[test]
+    fn squarify_map_f64() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mkmap = || -> HashMap<String, f64> {
+            [("a", 2.), ("b", 6.), ("c", 1.), ("d", 4.), ("e", 2.), ("f", 6.), ("g", 3.)]
+                .iter()
+                .map(|&(k, n)| (k.to_string(), n))
+                .collect()
+        };
+        let layout = squarify_map(container, mkmap());
+        let keys: Vec<&str> = layout.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["b", "f", "d", "g", "a", "e", "c"]);
+        for _ in 0..8 {
+            assert_eq!(squarify_map(container, mkmap()), layout);
+        }
+    }
+
+    #[test]
+    fn density_f32() {
+        let container = Rect { x: 0., y: 0., w: 10., h: 10. };
+        let mut sizes = vec![100.];
+        sizes.extend(&[1.; 20]);
+        let mut slice = mkslice::<f32>(&sizes);
+        squarify(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        let d = density(container, &slice[..], |&(_, _, r)| r, 1.);
+        assert!((d - 20. / 21.).abs() <= EPSILON, "d = {}", d);
+
+        // zoomed on the largest tile, the tiny ones are out of view
+        let view = slice[0].2;
+        assert_eq!(density(view, &slice[..], |&(_, _, r)| r, 1.), 0.);
+
+        let mut slice = mkslice::<f32>(&[1., 1., 1., 1.]);
+        squarify(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        assert_eq!(density(container, &slice[..], |&(_, _, r)| r, 1.), 0.);
+        assert_eq!(density(container, &slice[..0], |&(_, _, r)| r, 1.), 0.);
+    }
+
+    #[test]
+    fn brick_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 3. };
+        let mut slice = mkslice::<f32>(&[2., 4., 3., 3., 1., 2.]);
+        brick(container, &mut slice[..], |&(_, n, _)| n, 1., mkset_rect());
+        assert_eq!(
+            slice,
+            [
+                (0, 2.0, Rect { x: 0.0, y: 0.0, w: 2.0, h: 1.0 }),
+                (1, 4.0, Rect { x: 2.0, y: 0.0, w: 4.0, h: 1.0 }),
+                (2, 3.0, Rect { x: 0.0, y: 1.0, w: 3.0, h: 1.0 }),
+                (3, 3.0, Rect { x: 3.0, y: 1.0, w: 3.0, h: 1.0 }),
+                (4, 1.0, Rect { x: 0.0, y: 2.0, w: 2.0, h: 1.0 }),
+                (5, 2.0, Rect { x: 2.0, y: 2.0, w: 4.0, h: 1.0 })
+            ]
+        );
+        validate_coverage(container, &slice[..], |&(_, _, r)| r, 0.).unwrap();
+
+        // rows that wrap early are stretched to the container width too
+        let mut slice = mkslice::<f32>(&[2.1, 1.7, 2.3, 2.9, 1.3, 0.7]);
+        brick(container, &mut slice[..], |&(_, n, _)| n, 1., mkset_rect());
+        let rows: Vec<f32> = slice.iter().map(|&(_, _, r)| r.y).collect();
+        assert_eq!(rows, [0., 0., 1., 1., 2., 2.]);
+        for row in slice.chunks(2) {
+            let (a, b) = (row[0].2, row[1].2);
+            assert_eq!((a.x, a.max_x()), (0., b.x), "{:?} {:?}", a, b);
+            assert!((b.max_x() - 6.).abs() <= EPSILON * 10., "{:?}", b);
+            assert!((a.w / b.w - row[0].1 / row[1].1).abs() <= EPSILON * 10., "{:?} {:?}", a, b);
+        }
+        validate_coverage(container, &slice[..], |&(_, _, r)| r, EPSILON * 10.).unwrap();
+
+        // an item wider than the container gets a row on its own
+        let mut slice = mkslice::<f32>(&[8., 1.]);
+        brick(container, &mut slice[..], |&(_, n, _)| n, 1., mkset_rect());
+        assert_eq!(slice[0].2, Rect { x: 0.0, y: 0.0, w: 6.0, h: 1.0 });
+        assert_eq!(slice[1].2, Rect { x: 0.0, y: 1.0, w: 6.0, h: 1.0 });
+
+        let mut slice = mkslice::<f32>(&[2., 1.]);
+        brick(container, &mut slice[..], |&(_, n, _)| n, 0., mkset_rect());
+        for &(_, _, r) in &slice {
+            assert_eq!(r, Rect { x: 0., y: 0., w: 0., h: 0. });
+        }
+    }
+
+    #[test]
+    fn split_k_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        let regions = split_k(container, &slice[..], 3, |&(_, n, _)| n);
+        assert_eq!(
+            regions,
+            [
+                (Rect { x: 0.0, y: 0.0, w: 1.5, h: 4.0 }, 0..1),
+                (Rect { x: 1.5, y: 0.0, w: 2.5, h: 4.0 }, 1..3),
+                (Rect { x: 4.0, y: 0.0, w: 2.0, h: 4.0 }, 3..7),
+            ]
+        );
+        validate_coverage(container, &regions[..], |&(r, _)| r, 0.).unwrap();
+        for (r, range) in regions {
+            let size: f32 = slice[range].iter().map(|&(_, n, _)| n).sum();
+            assert!((r.w * r.h - size).abs() <= EPSILON);
+            assert!((size - 8.).abs() <= 2.);
+        }
+
+        let regions = split_k(container, &slice[..2], 3, |&(_, n, _)| n);
+        let ranges: Vec<Range<usize>> = regions.into_iter().map(|(_, range)| range).collect();
+        assert_eq!(ranges, [0..1, 1..1, 1..2]);
+    }
+
+    #[test]
+    fn squarify_reusing_strips_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let displacement = |a: &[(usize, f32, Rect<f32>)], b: &[(usize, f32, Rect<f32>)]| {
+            a.iter()
+                .zip(b)
+                .map(|((_, _, a), (_, _, b))| {
+                    (a.x - b.x).abs() + (a.y - b.y).abs() + (a.w - b.w).abs() + (a.h - b.h).abs()
+                })
+                .sum::<f32>()
+        };
+
+        let mut frame0 = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        let strips = squarify_reusing_strips(
+            container,
+            &mut frame0[..],
+            &[],
+            |&(_, n, _)| n,
+            mkset_rect11(EPSILON),
+        );
+        assert_eq!(strips, [2, 2, 1, 1, 1]);
+        let mut fresh = frame0.clone();
+        squarify(container, &mut fresh[..], |&(_, n, _)| n, mkset_rect());
+        assert_eq!(fresh, frame0);
+
+        let mut frame1 = mkslice::<f32>(&[6., 6., 5., 3., 2., 2., 1.]);
+        let reused = squarify_reusing_strips(
+            container,
+            &mut frame1[..],
+            &strips,
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        assert_eq!(reused, strips);
+        validate_coverage(container, &frame1[..], |&(_, _, r)| r, EPSILON * 10.).unwrap();
+
+        let mut fresh = frame1.clone();
+        let fresh_strips =
+            squarify_reusing_strips(container, &mut fresh[..], &[], |&(_, n, _)| n, mkset_rect());
+        assert_eq!(fresh_strips, [2, 1, 2, 1, 1]);
+        assert!(displacement(&frame0, &frame1) < displacement(&frame0, &fresh));
+    }
+
+    #[test]
+    fn rect_contains_f32() {
+        let parent = Rect { x: 1., y: 2., w: 6., h: 4. };
+        assert!(parent.contains_rect(&parent));
+        assert!(parent.contains_rect(&Rect { x: 2., y: 3., w: 1., h: 1. }));
+        assert!(!parent.contains_rect(&Rect { w: 6. + EPSILON * 8., ..parent }));
+        assert!(!parent.contains_rect(&Rect { y: 2. - EPSILON * 8., ..parent }));
+
+        assert!(parent.contains_point(1., 2.));
+        assert!(parent.contains_point(6.5, 5.5));
+        assert!(!parent.contains_point(7., 5.5));
+        assert!(!parent.contains_point(0.5, 3.));
+    }
+
+    #[test]
+    fn to_html_f32() {
+        let container = Rect { x: 1., y: 2., w: 6., h: 4. };
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        binary(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        let html = to_html(
+            container,
+            &slice[..],
+            |&(_, n, _)| n,
+            |&(_, _, r)| r,
+            |&(i, _, _)| format!("<{}>", i),
+        );
+        eprintln!("<!-- binary -->\n{}", html);
+        assert!(html.starts_with(
+            "<div style=\"position: relative; width: 6px; height: 4px;\">\n  <div style=\"position: absolute; left: 0px; top: 0px; width: 3px; height: 2px;\" data-size=\"6\">&lt;0&gt;</div>\n"
+        ));
+        assert_eq!(html.matches("<div").count(), slice.len() + 1);
+        for (i, _, r) in slice {
+            let div = format!(
+                r#"left: {}px; top: {}px; width: {}px; height: {}px;"#,
+                r.x - container.x,
+                r.y - container.y,
+                r.w,
+                r.h
+            );
+            assert!(html.contains(&div), "{} not found for item {}", div, i);
+        }
+    }
+
+    #[test]
+    fn squarify_equal_tiles_f32() {
+        // A row of 2 tiles at the top, a column of 2 tiles, then 2 rows of 1 tile
+        let container = Rect { x: 0., y: 0., w: 2.2, h: 3.3 };
+        let mut slice = mkslice::<f32>(&[1., 1., 1., 1., 1., 1.]);
+        squarify(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        assert!(slice[..4]
+            .chunks(2)
+            .any(|strip| strip[0].2.w != strip[1].2.w || strip[0].2.h != strip[1].2.h));
+
+        let options = SquarifyOptions::new().equal_tiles(true);
+        squarify_with(container, &mut slice[..], &options, |&(_, n, _)| n, mkset_rect());
+        for strip in slice[..4].chunks(2) {
+            assert_eq!((strip[0].2.w, strip[0].2.h), (strip[1].2.w, strip[1].2.h), "{:?}", slice);
+        }
+        assert_eq!(slice[1].2.y, slice[0].2.y);
+        assert_eq!(slice[3].2.x, slice[2].2.x);
+        validate_coverage(container, &slice[..], |&(_, _, r)| r, EPSILON * 10.).unwrap();
+    }
+
+    #[test]
+    fn squarify_multi_f32() {
+        let containers =
+            [Rect { x: 0., y: 0., w: 6., h: 4. }, Rect { x: 10., y: 0., w: 4., h: 6. }];
+        let mut slice: Vec<(usize, f32, Rect<f32>, usize)> = [6., 6., 4., 3., 2., 2., 1.]
+            .iter()
+            .enumerate()
+            .map(|(i, &n)| (i, n, Rect { x: 0., y: 0., w: 0., h: 0. }, usize::MAX))
+            .collect();
+        let mut idx = 0;
+        squarify_multi(
+            &containers,
+            &mut slice[..],
+            |&(_, n, _, _)| n,
+            |item, c, r| {
+                assert_eq!(item.0, idx, "f_item_set_rect must be called in stable order");
+                item.2 = r;
+                item.3 = c;
+                idx += 1;
+            },
+        );
+        assert_eq!(idx, slice.len());
+
+        for (c, container) in containers.iter().enumerate() {
+            let tiles: Vec<Rect<f32>> =
+                slice.iter().filter(|item| item.3 == c).map(|item| item.2).collect();
+            let size: f32 = slice.iter().filter(|item| item.3 == c).map(|item| item.1).sum();
+            assert_eq!(size, 12.);
+            assert!(tiles.iter().all(|r| container.contains_rect(r)), "{:?}", tiles);
+            validate_coverage(*container, &tiles[..], |&r| r, EPSILON * 10.).unwrap();
+        }
+    }
+
+    #[test]
+    fn binary_zero_sizes_f32() {
+        let container = Rect { x: 1., y: 2., w: 6., h: 4. };
+        let mut slice = mkslice::<f32>(&[1., 0., 0.]);
+        binary(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        assert_eq!(
+            slice,
+            [
+                (0, 1.0, Rect { x: 1.0, y: 2.0, w: 6.0, h: 4.0 }),
+                (1, 0.0, Rect { x: 7.0, y: 2.0, w: 0.0, h: 0.0 }),
+                (2, 0.0, Rect { x: 7.0, y: 2.0, w: 0.0, h: 0.0 })
+            ]
+        );
+
+        let mut slice = mkslice::<f32>(&[0., 0., 0.]);
+        binary(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        for (_, _, r) in slice {
+            assert_eq!(r, Rect { x: 1.0, y: 2.0, w: 0.0, h: 0.0 });
+        }
+    }
+
+    #[test]
+    fn focus_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        focus(container, &mut slice[..], 3, 0.5, |&(_, n, _)| n, mkset_rect());
+        assert_eq!(slice[3].2, Rect { x: 0., y: 0., w: 3., h: 4. });
+
+        let rest = Rect { x: 3., y: 0., w: 3., h: 4. };
+        let others: Vec<Rect<f32>> =
+            slice.iter().filter(|&&(i, _, _)| i != 3).map(|&(_, _, r)| r).collect();
+        assert!(others.iter().all(|r| rest.contains_rect(r)), "{:?}", others);
+        validate_coverage(rest, &others[..], |&r| r, EPSILON * 10.).unwrap();
+        // other tiles keep their relative sizes
+        let scale = 12. / 21.;
+        for &(i, n, r) in &slice {
+            if i != 3 {
+                assert!((r.w * r.h - n * scale).abs() <= EPSILON * 10., "{:?}", slice);
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct Node {
+        size: f32,
+        children: Vec<Node>,
+        rect: Rect<f32>,
+        path: Vec<usize>,
+    }
+
+    fn mktree(sizes: &[&[f32]]) -> Vec<Node> {
+        let node = |size, children| Node {
+            size,
+            children,
+            rect: Rect { x: 0., y: 0., w: 0., h: 0. },
+            path: vec![],
+        };
+        sizes
+            .iter()
+            .map(|children| {
+                let children: Vec<Node> = children.iter().map(|&n| node(n, vec![])).collect();
+                node(children.iter().map(|c| c.size).sum(), children)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn squarify_tree_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut tree = mktree(&[&[6., 6.], &[4., 3.], &[2., 2., 1.]]);
+        squarify_tree(
+            container,
+            &mut tree[..],
+            |n| n.size,
+            |n| &mut n.children[..],
+            |n, path, r| {
+                n.path = path.to_vec();
+                n.rect = r;
+            },
+        );
+        validate_coverage(container, &tree[..], |n| n.rect, EPSILON * 10.).unwrap();
+        for (p, parent) in tree.iter().enumerate() {
+            assert_eq!(parent.path, [p]);
+            validate_coverage(parent.rect, &parent.children[..], |n| n.rect, EPSILON * 10.)
+                .unwrap();
+            for (c, child) in parent.children.iter().enumerate() {
+                assert_eq!(child.path, [p, c]);
+                assert!((child.rect.w * child.rect.h - child.size).abs() <= EPSILON * 10.);
+            }
+        }
+    }
+
+    #[test]
+    fn squarify_sorted_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut slice = mkslice::<f32>(&[2., 6., 1., 3., 6., 2., 4.]);
+        assert!(!is_sorted_desc_by_size(&slice[..], |&(_, n, _)| n));
+        sort_desc_by_size(&mut slice[..], |&(_, n, _)| n);
+        assert!(is_sorted_desc_by_size(&slice[..], |&(_, n, _)| n));
+        let order: Vec<usize> = slice.iter().map(|&(i, _, _)| i).collect();
+        assert_eq!(order, [1, 4, 6, 3, 0, 5, 2]);
+
+        let mut expected = slice.clone();
+        squarify(container, &mut expected[..], |&(_, n, _)| n, |(_, _, item_r), r| *item_r = r);
+        squarify_sorted(container, &mut slice[..], |&(_, n, _)| n, |(_, _, item_r), r| *item_r = r);
+        assert_eq!(slice, expected);
+    }
+
+    #[test]
+    fn bin_by_size_f64() {
+        let sizes = [1000f64, 400., 40., 25., 4., 1.];
+        assert_eq!(bin_by_size(&sizes[..], |&n| n, 3), [2, 2, 1, 1, 0, 0]);
+        assert_eq!(bin_by_size(&sizes[..], |&n| n, 1), [0, 0, 0, 0, 0, 0]);
+        assert_eq!(bin_by_size(&[0f64, 5., 5.][..], |&n| n, 3), [0, 0, 0]);
+    }
+
+    #[test]
+    fn squarify_pin_orientation_f32() {
+        let container = Rect { x: 0., y: 0., w: 4.5, h: 4. };
+        let mut adaptive = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify(container, &mut adaptive[..], |&(_, n, _)| n, mkset_rect());
+
+        let mut pinned = adaptive.clone();
+        let options = SquarifyOptions::new().pin_orientation(true);
+        squarify_with(container, &mut pinned[..], &options, |&(_, n, _)| n, mkset_rect());
+        assert_ne!(pinned, adaptive);
+        validate_coverage(container, &pinned[..], |&(_, _, r)| r, EPSILON * 10.).unwrap();
+        // every strip is a column
+        let mut x = 0.;
+        for &(_, _, r) in &pinned {
+            assert!(r.x >= x, "{:?}", pinned);
+            x = r.x;
+        }
+    }
+
+    #[test]
+    fn rect_subtract_i32() {
+        let r = Rect { x: 0, y: 0, w: 6, h: 4 };
+        let hole = Rect { x: 2, y: 1, w: 2, h: 2 };
+        assert_eq!(r.intersection(&hole), Some(hole));
+        assert_eq!(
+            r.subtract(&hole),
+            [
+                Some(Rect { x: 0, y: 0, w: 6, h: 1 }),
+                Some(Rect { x: 0, y: 3, w: 6, h: 1 }),
+                Some(Rect { x: 0, y: 1, w: 2, h: 2 }),
+                Some(Rect { x: 4, y: 1, w: 2, h: 2 }),
+            ]
+        );
+
+        let corner = Rect { x: 4, y: -1, w: 4, h: 3 };
+        assert_eq!(r.intersection(&corner), Some(Rect { x: 4, y: 0, w: 2, h: 2 }));
+        assert_eq!(
+            r.subtract(&corner),
+            [
+                None,
+                Some(Rect { x: 0, y: 2, w: 6, h: 2 }),
+                Some(Rect { x: 0, y: 0, w: 4, h: 2 }),
+                None
+            ]
+        );
+
+        let pieces = r.subtract(&Rect { x: -1, y: -1, w: 8, h: 8 });
+        assert_eq!(pieces.iter().flatten().count(), 0);
+
+        let outside = Rect { x: 6, y: 0, w: 2, h: 2 };
+        assert_eq!(r.intersection(&outside), None);
+        assert_eq!(r.subtract(&outside), [Some(r), None, None, None]);
+    }
+
+    /// Signature of the algorithms compared in tests
+    type AlgorithmFn = fn(
+        Rect<f32>,
+        &mut [(usize, f32, Rect<f32>)],
+        fn(&(usize, f32, Rect<f32>)) -> f32,
+        Box<dyn FnMut(&mut (usize, f32, Rect<f32>), Rect<f32>)>,
+    );
+
+    #[test]
+    fn average_aspect_ratio_baseline_f32() {
+        // Update these baselines only if an output quality change is intended
+        let baselines: [(&str, AlgorithmFn, f32); 6] = [
+            ("slice", slice, 14.999998),
+            ("dice", dice, 6.666667),
+            ("binary", binary, 1.8883116),
+            ("squarify", squarify, 1.675926),
+            ("ordered_pivot_by_middle", ordered_pivot_by_middle, 1.9720238),
+            ("ordered_pivot_by_size", ordered_pivot_by_size, 2.1019573),
+        ];
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        for &(name, algorithm, baseline) in baselines.iter() {
+            let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+            algorithm(container, &mut slice[..], |&(_, n, _)| n, Box::new(mkset_rect()));
+            let ratio = average_aspect_ratio(&slice[..], |&(_, _, r)| r);
+            assert!(
+                (ratio - baseline).abs() <= EPSILON,
+                "{} average aspect ratio changed from {} to {}",
+                name,
+                baseline,
+                ratio
+            );
+        }
+        assert_eq!(average_aspect_ratio(&[][..], |&r: &Rect<f32>| r), 0.);
+        assert_eq!(Rect { x: 0., y: 0., w: 2., h: 8. }.aspect_ratio(), 4.);
+    }
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn squarify_f16() {
+        use half::f16;
+
+        let f = f16::from_f32;
+        let container = Rect { x: f(0.), y: f(0.), w: f(6.), h: f(4.) };
+        let mut slice = mkslice::<f16>(&[f(6.), f(6.), f(4.), f(3.), f(2.), f(2.), f(1.)]);
+        squarify(container, &mut slice[..], |&(_, n, _)| n, mkset_rect11(f(0.01)));
+        validate_coverage(container, &slice[..], |&(_, _, r)| r, f(0.01)).unwrap();
+        binary(container, &mut slice[..], |&(_, n, _)| n, mkset_rect11(f(0.01)));
+        validate_coverage(container, &slice[..], |&(_, _, r)| r, f(0.01)).unwrap();
+    }
+
+    #[test]
+    fn squarify_stable_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let frames: [[f32; 7]; 5] = [
+            [6., 6., 4., 3., 2., 2., 1.],
+            [6., 6., 5., 3., 2., 2., 1.],
+            [6., 5.5, 5., 3., 2.5, 2., 1.],
+            [6., 5.5, 5., 3.5, 2.5, 1.5, 1.],
+            [6.5, 5.5, 4., 3.5, 2.5, 1.5, 1.5],
+        ];
+        let rects = |slice: &[(usize, f32, Rect<f32>)]| -> Vec<Rect<f32>> {
+            slice.iter().map(|&(_, _, r)| r).collect()
+        };
+
+        let mut previous_plain = Vec::new();
+        let mut previous_stable: Vec<Rect<f32>> = Vec::new();
+        let (mut moves_plain, mut moves_stable) = (0., 0.);
+        for sizes in frames.iter() {
+            let mut plain = mkslice::<f32>(sizes);
+            squarify(container, &mut plain[..], |&(_, n, _)| n, mkset_rect());
+            let mut stable = mkslice::<f32>(sizes);
+            squarify_stable(
+                container,
+                &mut stable[..],
+                &previous_stable,
+                |&(_, n, _)| n,
+                mkset_rect(),
+            );
+            validate_coverage(container, &stable[..], |&(_, _, r)| r, EPSILON * 10.).unwrap();
+            moves_plain += stability(&previous_plain, &rects(&plain));
+            moves_stable += stability(&previous_stable, &rects(&stable));
+            previous_plain = rects(&plain);
+            previous_stable = rects(&stable);
+        }
+        assert!(moves_stable < moves_plain, "{} >= {}", moves_stable, moves_plain);
+        assert_eq!(stability(&previous_stable, &previous_stable), 0.);
+    }
+
+    #[test]
+    fn squarify_splits_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        let splits = squarify_splits(container, &slice[..], |&(_, n, _)| n);
+        assert_eq!(splits, [2, 4, 5, 6, 7]);
+
+        squarify(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        let mut start = 0;
+        for &end in &splits {
+            let strip = &slice[start..end];
+            let (_, _, r0) = strip[0];
+            let is_column = strip.iter().all(|&(_, _, r)| r.x == r0.x && r.w == r0.w);
+            let is_row = strip.iter().all(|&(_, _, r)| r.y == r0.y && r.h == r0.h);
+            assert!(is_column || is_row, "{:?} is not a strip", strip);
+            start = end;
+        }
+
+        let strips =
+            squarify_reusing_strips(container, &mut slice[..], &[], |&(_, n, _)| n, mkset_rect());
+        let ends: Vec<usize> = strips
+            .iter()
+            .scan(0, |end, n| {
+                *end += n;
+                Some(*end)
+            })
+            .collect();
+        assert_eq!(ends, splits);
+    }
+
+    #[test]
+    fn squarify_with_margins_f32() {
+        let container = Rect { x: 0., y: 0., w: 8., h: 6. };
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify_with_margins(
+            container,
+            (1., 0.5, 1., 1.5),
+            &mut slice[..],
+            |&(_, n, _)| n,
+            mkset_rect11(EPSILON),
+        );
+        assert_eq!(container, Rect { x: 0., y: 0., w: 8., h: 6. });
+        let inner = Rect { x: 1.5, y: 1., w: 6., h: 4. };
+        assert!(slice.iter().all(|(_, _, r)| inner.contains_rect(r)), "{:?}", slice);
+        validate_coverage(inner, &slice[..], |&(_, _, r)| r, EPSILON * 10.).unwrap();
+
+        assert_eq!(container.inset(4., 5., 4., 5.), Rect { x: 5., y: 4., w: 0., h: 0. });
+    }
+
+    #[test]
+    fn squarify_weighted_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let importance = [1., 1., 1., 1., 1., 1., 36.];
+        let mut by_size = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify(container, &mut by_size[..], |&(_, n, _)| n, mkset_rect());
+
+        let mut weighted = by_size.clone();
+        squarify_weighted(
+            container,
+            &mut weighted[..],
+            |&(_, n, _)| n,
+            |&(i, _, _)| importance[i],
+            0.,
+            mkset_rect(),
+        );
+        assert_eq!(weighted, by_size);
+
+        squarify_weighted(
+            container,
+            &mut weighted[..],
+            |&(_, n, _)| n,
+            |&(i, _, _)| importance[i],
+            0.5,
+            mkset_rect(),
+        );
+        let area = |r: Rect<f32>| r.w * r.h;
+        assert!(area(weighted[6].2) > area(by_size[6].2));
+        assert!(area(weighted[6].2) > area(weighted[5].2));
+        validate_coverage(container, &weighted[..], |&(_, _, r)| r, EPSILON * 10.).unwrap();
+    }
+
+    #[test]
+    fn binary_const_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut expected = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        binary(container, &mut expected[..], |&(_, n, _)| n, mkset_rect());
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        binary_const::<_, _, _, _, 8>(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        assert_eq!(slice, expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "binary_const capacity exceeded")]
+    fn binary_const_capacity_f32() {
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        binary_const::<_, _, _, _, 4>(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut slice[..],
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+    }
+
+    #[test]
+    fn merge_adjacent_below_f32() {
+        let container = Rect { x: 0., y: 0., w: 4., h: 5. };
+        let mut items = mkslice::<f32>(&[10., 0.5, 0.5, 0.5, 8., 0.5]);
+        slice(container, &mut items[..], |&(_, n, _)| n, mkset_rect11(EPSILON));
+        let mut merged = Vec::new();
+        merge_adjacent_below(
+            &items[..],
+            |&(_, n, _)| n,
+            1.,
+            |&(_, _, r)| r,
+            EPSILON,
+            |range, r| merged.push((range, r)),
+        );
+        assert_eq!(merged, [(1..4, Rect { x: 0., y: 2.5, w: 4., h: 0.375 })]);
+        assert_eq!(merged[0].1.w * merged[0].1.h, 1.5);
+
+        // Squarify strip edges that aren't exactly representable
+        let container = Rect { x: 0.3, y: 0.1, w: 3.1, h: 7.3 };
+        let mut items = mkslice::<f32>(&[9.1, 0.7, 0.7, 0.7, 9.8]);
+        squarify(container, &mut items[..], |&(_, n, _)| n, mkset_rect());
+        let mut merged = Vec::new();
+        merge_adjacent_below(
+            &items[..],
+            |&(_, n, _)| n,
+            1.,
+            |&(_, _, r)| r,
+            EPSILON * 10.,
+            |range, r| merged.push((range, r)),
+        );
+        assert_eq!(merged.len(), 1, "{:?}", merged);
+        let (range, r) = merged[0].clone();
+        assert_eq!(range, 1..4);
+        assert!((r.w * r.h - 2.1 * 3.1 * 7.3 / 21.).abs() <= EPSILON * 10., "{:?}", r);
+    }
+
+    #[test]
+    fn zero_area_container_f32() {
+        let algorithms: [(&str, AlgorithmFn); 5] = [
+            ("slice", slice),
+            ("dice", dice),
+            ("squarify", squarify),
+            ("ordered_pivot_by_middle", ordered_pivot_by_middle),
+            ("ordered_pivot_by_size", ordered_pivot_by_size),
+        ];
+        for container in [Rect { x: 0., y: 0., w: 0., h: 0. }, Rect { x: 1., y: 2., w: 5., h: 0. }]
+        {
+            for &(name, algorithm) in algorithms.iter() {
+                let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+                for item in slice.iter_mut() {
+                    item.2 = Rect { x: f32::NAN, y: f32::NAN, w: f32::NAN, h: f32::NAN };
+                }
+                algorithm(container, &mut slice[..], |&(_, n, _)| n, Box::new(mkset_rect()));
+                for &(_, _, r) in &slice {
+                    assert_eq!(r, Rect { w: 0., h: 0., ..container }, "{} {:?}", name, container);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn outline_l_shape_f32() {
+        let rects = [Rect { x: 0., y: 0., w: 2., h: 1. }, Rect { x: 0., y: 1., w: 1., h: 1. }];
+        assert_eq!(
+            outline(&rects, |&r| r),
+            vec![(0., 0.), (2., 0.), (2., 1.), (1., 1.), (1., 2.), (0., 2.)]
+        );
+
+        let rects = [Rect { x: 0., y: 0., w: 2., h: 2. }, Rect { x: 2., y: 0., w: 1., h: 2. }];
+        assert_eq!(outline(&rects, |&r| r), vec![(0., 0.), (3., 0.), (3., 2.), (0., 2.)]);
+        assert!(outline(&[] as &[Rect<f32>], |&r| r).is_empty());
+    }
+
+    #[test]
+    fn squarify_canonical_permutations_f32() {
+        let items = [("a", 6.), ("b", 6.), ("c", 4.), ("d", 3.), ("e", 2.), ("f", 2.), ("g", 1.)];
+        let shuffled =
+            [("f", 2.), ("b", 6.), ("g", 1.), ("d", 3.), ("a", 6.), ("e", 2.), ("c", 4.)];
+        let by_key = |items: &[(&'static str, f32)]| {
+            let mut rects: Vec<(&str, Rect<f32>)> = squarify_canonical(
+                Rect { x: 0., y: 0., w: 6., h: 4. },
+                items,
+                |&(_, n)| n,
+                |&(k, _)| k,
+            )
+            .into_iter()
+            .map(|(idx, r)| (items[idx].0, r))
+            .collect();
+            rects.sort_by_key(|&(k, _)| k);
+            rects
+        };
+        assert_eq!(by_key(&items), by_key(&shuffled));
+    }
+
+    #[test]
+    fn alignment_score_slice_vs_squarify_f32() {
+        let score = |algorithm: AlgorithmFn| {
+            let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+            algorithm(
+                Rect { x: 0., y: 0., w: 6., h: 4. },
+                &mut slice[..],
+                |&(_, n, _)| n,
+                Box::new(mkset_rect()),
+            );
+            alignment_score(&slice, |&(_, _, r)| r, EPSILON)
+        };
+        let (slice_score, squarify_score) = (score(slice), score(squarify));
+        assert!((slice_score - 48. / 7.).abs() < EPSILON, "{}", slice_score);
+        assert!(slice_score > squarify_score, "{} <= {}", slice_score, squarify_score);
+        assert_eq!(alignment_score(&[] as &[Rect<f32>], |&r| r, EPSILON), 0.);
+    }
+
+    #[test]
+    fn squarify_with_aspect_square_tile_f32() {
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify_with_aspect(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut slice[..],
+            |&(_, n, _)| n,
+            |&(i, _, _)| if i == 2 { Some(1.) } else { None },
+            mkset_rect(),
+        );
+        let r = slice[2].2;
+        assert!((r.w * r.h - 4.).abs() < EPSILON, "{:?}", r);
+        assert!((r.w - r.h).abs() < EPSILON, "{:?}", r);
+        for &(i, n, r) in &slice {
+            assert!(r.w > 0. && r.h > 0., "{} {} {:?}", i, n, r);
+        }
+    }
+
+    #[test]
+    fn squarify_pairs_doc_example_f32() {
+        const R0: Rect<f32> = Rect { x: 0., y: 0., w: 0., h: 0. };
+        let mut pairs = [(6., R0), (6., R0), (4., R0), (3., R0), (2., R0), (2., R0), (1., R0)];
+        squarify_pairs(Rect { x: 0., y: 0., w: 6., h: 4. }, &mut pairs[..]);
+        assert_eq!(
+            pairs,
+            [
+                (6.0, Rect { x: 0.0, y: 0.0, w: 3.0, h: 2.0 }),
+                (6.0, Rect { x: 0.0, y: 2.0, w: 3.0, h: 2.0 }),
+                (4.0, Rect { x: 3.0, y: 0.0, w: 1.7142857, h: 2.3333333 }),
+                (3.0, Rect { x: 4.714286, y: 0.0, w: 1.2857141, h: 2.3333333 }),
+                (2.0, Rect { x: 3.0, y: 2.3333333, w: 1.1999999, h: 1.6666667 }),
+                (2.0, Rect { x: 4.2, y: 2.3333333, w: 1.1999999, h: 1.6666667 }),
+                (1.0, Rect { x: 5.3999996, y: 2.3333333, w: 0.60000014, h: 1.6666667 })
+            ]
+        );
+
+        let mut expected = pairs;
+        binary(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut expected[..],
+            |&(n, _)| n,
+            |(_, item_r), r| *item_r = r,
+        );
+        binary_pairs(Rect { x: 0., y: 0., w: 6., h: 4. }, &mut pairs[..]);
+        assert_eq!(pairs, expected);
+    }
+
+    #[test]
+    fn spatial_index_matches_linear_scan_f32() {
+        let mut seed = 0x2545_f491_u32;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed
+        };
+        let sizes: Vec<f32> = (0..500).map(|_| (next() % 100 + 1) as f32).collect();
+        let mut slice = mkslice(&sizes);
+        let container = Rect { x: 0., y: 0., w: 300., h: 200. };
+        squarify(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        let rects: Vec<Rect<f32>> = slice.iter().map(|&(_, _, r)| r).collect();
+        let index = SpatialIndex::from_layout(container, &rects);
+        for _ in 0..2000 {
+            let x = (next() % 30_000) as f32 / 100.;
+            let y = (next() % 20_000) as f32 / 100.;
+            let expected = rects.iter().position(|r| r.contains_point(x, y));
+            assert_eq!(index.query_point(x, y), expected, "({}, {})", x, y);
+        }
+        assert_eq!(index.query_point(-1., 0.), None);
+
+        let query = Rect { x: 50., y: 50., w: 40., h: 30. };
+        let expected: Vec<usize> =
+            (0..rects.len()).filter(|&idx| rects[idx].intersection(&query).is_some()).collect();
+        assert_eq!(index.query_rect(&query), expected);
+    }
+
+    #[test]
+    fn squarify_first_strip_two_items_f32() {
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify_first_strip(
+            Rect { x: 0., y: 0., w: 8., h: 3. },
+            &mut slice[..],
+            2,
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        // The first two tiles stack into a column of the full height
+        let (a, b) = (slice[0].2, slice[1].2);
+        assert_eq!((a.x, a.w), (b.x, b.w));
+        assert_eq!(a.y, 0.);
+        assert!((b.y + b.h - 3.).abs() < EPSILON);
+
+        let rest = Rect { x: a.w, y: 0., w: 8. - a.w, h: 3. };
+        let mut expected = mkslice::<f32>(&[4., 3., 2., 2., 1.]);
+        squarify(rest, &mut expected[..], |&(_, n, _)| n, mkset_rect());
+        for (&(_, _, r), &(_, _, e)) in slice[2..].iter().zip(&expected) {
+            assert!(displacement(&r, &e) < EPSILON, "{:?} != {:?}", r, e);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "first_k out of bounds")]
+    fn squarify_first_strip_out_of_bounds_f32() {
+        let mut slice = mkslice::<f32>(&[6., 6.]);
+        squarify_first_strip(
+            Rect { x: 0., y: 0., w: 3., h: 4. },
+            &mut slice[..],
+            3,
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+    }
+
+    #[test]
+    fn squarify_diff_deltas_f32() {
+        let before = [6., 6., 4., 3., 2., 2., 1.];
+        let after = [12., 3., 4., 3., 2., 2., 1.];
+        let items: Vec<(f32, f32)> = before.iter().copied().zip(after.iter().copied()).collect();
+        let diff =
+            squarify_diff(Rect { x: 0., y: 0., w: 6., h: 4. }, &items, |&(b, _)| b, |&(_, a)| a);
+
+        let mut expected = mkslice::<f32>(&before);
+        squarify(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut expected[..],
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        assert_eq!(diff.len(), expected.len());
+        for (&(r, _), &(_, _, e)) in diff.iter().zip(&expected) {
+            assert_eq!(r, e);
+        }
+        assert_eq!(diff[0].1, 1.);
+        assert_eq!(diff[1].1, -0.5);
+        assert_eq!(diff[2].1, 0.);
+    }
+
+    #[test]
+    fn rect_quadrants_u32() {
+        for &(w, h) in &[(6u32, 4u32), (7, 5), (1, 1)] {
+            let rect = Rect { x: 3, y: 2, w, h };
+            let quadrants = rect.quadrants();
+            let area: u32 = quadrants.iter().map(|q| q.w * q.h).sum();
+            assert_eq!(area, w * h);
+            for (i, a) in quadrants.iter().enumerate() {
+                assert!(rect.contains_rect(a));
+                for b in &quadrants[i + 1..] {
+                    assert_eq!(a.intersection(b), None);
+                }
+            }
+        }
+        assert_eq!(
+            Rect { x: 0, y: 0, w: 5, h: 3 }.quadrants(),
+            [
+                Rect { x: 0, y: 0, w: 2, h: 1 },
+                Rect { x: 2, y: 0, w: 3, h: 1 },
+                Rect { x: 0, y: 1, w: 2, h: 2 },
+                Rect { x: 2, y: 1, w: 3, h: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn resize_layout_doubles_f32() {
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify(Rect { x: 0., y: 0., w: 6., h: 4. }, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        let old_rects: Vec<Rect<f32>> = slice.iter().map(|&(_, _, r)| r).collect();
+        let new_rects = resize_layout(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            Rect { x: 0., y: 0., w: 12., h: 8. },
+            &old_rects,
+        );
+        for (old, new) in old_rects.iter().zip(&new_rects) {
+            assert_eq!(*new, old.map(|v| v * 2.));
+        }
+    }
+
+    #[test]
+    fn fill_matches_slice_and_dice_f32() {
+        let algorithms: [(Axis, AlgorithmFn); 2] =
+            [(Axis::Vertical, slice), (Axis::Horizontal, dice)];
+        for &(axis, algorithm) in algorithms.iter() {
+            let mut expected = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+            algorithm(
+                Rect { x: 0., y: 0., w: 6., h: 4. },
+                &mut expected[..],
+                |&(_, n, _)| n,
+                Box::new(mkset_rect()),
+            );
+            let mut filled = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+            fill(
+                Rect { x: 0., y: 0., w: 6., h: 4. },
+                &mut filled[..],
+                axis,
+                |&(_, n, _)| n,
+                mkset_rect(),
+            );
+            assert_eq!(filled, expected, "{:?}", axis);
+        }
+    }
+
+    #[test]
+    fn rect_unit_round_trip_f32() {
+        let container = Rect { x: 10., y: 20., w: 6., h: 4. };
+        let tile = Rect { x: 13., y: 21., w: 1.5, h: 2. };
+        let unit = tile.to_unit(&container);
+        assert_eq!(unit, Rect { x: 0.5, y: 0.25, w: 0.25, h: 0.5 });
+        assert!(displacement(&unit.from_unit(&container), &tile) < EPSILON);
+        assert_eq!(container.to_unit(&container), Rect { x: 0., y: 0., w: 1., h: 1. });
+    }
+
+    #[test]
+    fn squarify_tree_with_axis_levels_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut tree = mktree(&[&[6., 6.], &[4., 3.], &[2., 2., 1.]]);
+        squarify_tree_with_axis(
+            container,
+            &mut tree[..],
+            |n| n.size,
+            |n| &mut n.children[..],
+            |depth| match depth {
+                0 => Some(Axis::Horizontal),
+                _ => Some(Axis::Vertical),
+            },
+            |n, path, r| {
+                n.path = path.to_vec();
+                n.rect = r;
+            },
+        );
+        validate_coverage(container, &tree[..], |n| n.rect, EPSILON * 10.).unwrap();
+        for parent in &tree {
+            // Root nodes are laid out left to right, spanning the whole height
+            assert_eq!((parent.rect.y, parent.rect.h), (0., 4.));
+            validate_coverage(parent.rect, &parent.children[..], |n| n.rect, EPSILON * 10.)
+                .unwrap();
+            for child in &parent.children {
+                // Children are stacked top to bottom, spanning their parent width
+                assert_eq!((child.rect.x, child.rect.w), (parent.rect.x, parent.rect.w));
+            }
+        }
+    }
+
+    #[test]
+    fn assert_exact_tiling_divisible_i64() {
+        let container = Rect { x: 0i64, y: 0, w: 6, h: 4 };
+        let mut slice = mkslice::<i64>(&[12, 6, 6]);
+        dice(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        assert_exact_tiling(container, &slice, |&(_, _, r)| r);
+    }
+
+    #[test]
+    fn assert_exact_tiling_not_divisible_i64() {
+        // 3 equal items in a 7⨯3 container, the remainder going to the first tile
+        let container = Rect { x: 1i64, y: 1, w: 7, h: 3 };
+        let tiles = [
+            Rect { x: 1, y: 1, w: 3, h: 3 },
+            Rect { x: 4, y: 1, w: 2, h: 3 },
+            Rect { x: 6, y: 1, w: 2, h: 3 },
+        ];
+        assert_exact_tiling(container, &tiles, |&r| r);
+    }
+
+    #[test]
+    #[should_panic(expected = "overlaps")]
+    fn assert_exact_tiling_overlap_i64() {
+        let container = Rect { x: 0i64, y: 0, w: 7, h: 3 };
+        let tiles = [
+            Rect { x: 0, y: 0, w: 4, h: 3 },
+            Rect { x: 3, y: 0, w: 2, h: 3 },
+            Rect { x: 5, y: 0, w: 1, h: 3 },
+        ];
+        assert_exact_tiling(container, &tiles, |&r| r);
+    }
+
+    #[test]
+    fn banded_two_equal_bands_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        banded(container, &mut slice[..], &[2., 2.], |&(_, n, _)| n, mkset_rect());
+        validate_coverage(container, &slice, |&(_, _, r)| r, EPSILON).unwrap();
+
+        let bands = [Rect { h: 2., ..container }, Rect { y: 2., h: 2., ..container }];
+        for band in &bands {
+            let mut in_band: Vec<_> =
+                slice.iter().copied().filter(|&(_, _, r)| band.contains_rect(&r)).collect();
+            let size: f32 = in_band.iter().map(|&(_, n, _)| n).sum();
+            assert_eq!(size, 12.);
+            let expected = in_band.clone();
+            squarify(*band, &mut in_band[..], |&(_, n, _)| n, |(_, _, item_r), r| *item_r = r);
+            assert_eq!(in_band, expected);
+        }
+    }
+
+    #[test]
+    fn layout_visible_left_half_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        let layout = Layout::from_items(container, &slice, |&(_, _, r)| r);
+        let viewport = Rect { w: 3., ..container };
+        let visible: Vec<usize> = layout.visible(&viewport).map(|(idx, _)| idx).collect();
+        assert_eq!(visible, [0, 1]);
+        for (idx, r) in layout.visible(&viewport) {
+            assert_eq!(r, layout.rects[idx]);
+            assert!(viewport.contains_rect(&r));
+        }
+    }
+
+    #[test]
+    fn squarify_visit_matches_squarify_f32() {
+        let mut expected = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut expected[..],
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        let slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        let mut visited = Vec::new();
+        squarify_visit(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &slice[..],
+            |&(_, n, _)| n,
+            |idx, r| visited.push((idx, r)),
+        );
+        let expected: Vec<(usize, Rect<f32>)> = expected.iter().map(|&(i, _, r)| (i, r)).collect();
+        assert_eq!(visited, expected);
+    }
+
+    #[test]
+    fn squarify_container_aspect_wide_f32() {
+        let container = Rect { x: 0., y: 0., w: 12., h: 4. };
+        let sizes = [8., 6., 6., 5., 4., 4., 3., 3., 3., 2., 2., 2.];
+        let distance_to_3 = |algorithm: AlgorithmFn| {
+            let mut slice = mkslice::<f32>(&sizes);
+            algorithm(container, &mut slice[..], |&(_, n, _)| n, Box::new(mkset_rect()));
+            validate_coverage(container, &slice, |&(_, _, r)| r, EPSILON).unwrap();
+            slice.iter().map(|&(_, _, r)| (r.w / r.h / 3.).ln().abs()).sum::<f32>()
+                / sizes.len() as f32
+        };
+        let (container_aspect, square) =
+            (distance_to_3(squarify_container_aspect), distance_to_3(squarify));
+        assert!(container_aspect < square, "{} >= {}", container_aspect, square);
+    }
+
+    #[cfg(feature = "count-ops")]
+    type CountedAlgorithm =
+        dyn Fn(&mut [(usize, f32, Rect<f32>)], &dyn Fn(&(usize, f32, Rect<f32>)) -> f32);
+
+    /// Layout `n` items of decreasing sizes with `algorithm`, returning the number of
+    /// size calls and of aspect ratio computations
+    #[cfg(feature = "count-ops")]
+    fn count_ops_of(n: usize, algorithm: &CountedAlgorithm) -> (usize, usize) {
+        use std::cell::Cell;
+
+        let sizes: Vec<f32> = (1..=n).rev().map(|n| n as f32).collect();
+        let mut slice = mkslice(&sizes);
+        let size_calls = Cell::new(0);
+        count_ops::reset();
+        algorithm(&mut slice[..], &|&(_, n, _)| {
+            size_calls.set(size_calls.get() + 1);
+            n
+        });
+        (size_calls.get(), count_ops::ratios())
+    }
+
+    #[test]
+    #[cfg(feature = "count-ops")]
+    fn count_ops_growth_f32() {
+        let rect = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let growth = |algorithm: &CountedAlgorithm| {
+            let (calls0, ratios0) = count_ops_of(400, algorithm);
+            let (calls1, ratios1) = count_ops_of(1600, algorithm);
+            (calls1 as f32 / calls0 as f32, ratios1 as f32 / ratios0 as f32, calls1)
+        };
+
+        // O(3⨯n): linear, a few size calls per item
+        let (calls, ratios, calls1) =
+            growth(&move |items, size| squarify(rect, items, size, mkset_rect()));
+        assert!(calls < 4.2 && ratios < 4.2, "{} {}", calls, ratios);
+        assert!(calls1 <= 4 * 1600, "{}", calls1);
+
+        // O(2⨯n⨯log_4(n)): slightly more than linear
+        let (calls, ratios, _) =
+            growth(&move |items, size| ordered_pivot_by_middle(rect, items, size, mkset_rect()));
+        assert!(calls > 4. && calls < 6., "{}", calls);
+        assert!(ratios > 4. && ratios < 6., "{}", ratios);
+
+        // O(n^2): at most quadratic, and more than the pivot by middle
+        let (calls, ratios, _) =
+            growth(&move |items, size| ordered_pivot_by_size(rect, items, size, mkset_rect()));
+        assert!(calls > 6. && calls <= 16., "{}", calls);
+        assert!(ratios > 6. && ratios <= 16., "{}", ratios);
+    }
+
+    #[test]
+    fn squarify_grouped_gutters_f32() {
+        let container = Rect { x: 0., y: 0., w: 12., h: 4. };
+        let groups = [0, 0, 1, 1, 2, 2, 2];
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify_grouped(
+            container,
+            &mut slice[..],
+            |&(i, _, _)| groups[i],
+            |size| size / 12.,
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        let bounds = |range: Range<usize>| {
+            let rects: Vec<Rect<f32>> = slice[range].iter().map(|&(_, _, r)| r).collect();
+            let bounds = rects.iter().skip(1).fold(rects[0], |b, r| b.union(r));
+            // Tiles of a group are contiguous: they cover their bounding box
+            validate_coverage(bounds, &rects, |&r| r, EPSILON).unwrap();
+            bounds
+        };
+        let (a, b, c) = (bounds(0..2), bounds(2..4), bounds(4..7));
+        assert!((b.x - (a.x + a.w) - (1. + 7. / 12.) / 2.).abs() < EPSILON, "{:?} {:?}", a, b);
+        assert!((c.x - (b.x + b.w) - 0.5).abs() < EPSILON, "{:?} {:?}", b, c);
+        assert_eq!(a.x, 0.);
+        assert!((c.x + c.w - 12.).abs() < EPSILON);
+
+        // Gutters larger than the container are scaled down
+        squarify_grouped(
+            container,
+            &mut slice[..],
+            |&(i, _, _)| groups[i],
+            |_| 100.,
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        for &(_, _, r) in &slice {
+            assert!(r.w >= 0. && r.h >= 0., "{:?}", r);
+        }
+    }
+
+    #[test]
+    fn weighted_centroid_biased_f32() {
+        let rects = [Rect { x: 0., y: 0., w: 3., h: 2. }, Rect { x: 3., y: 0., w: 1., h: 2. }];
+        let (x, y) = weighted_centroid(&rects, |&r| r);
+        // (1.5⨯6 + 3.5⨯2) / 8
+        assert_eq!((x, y), (2., 1.));
+        assert!(x < 2.5);
+        assert_eq!(weighted_centroid(&[] as &[Rect<f32>], |&r| r), (0., 0.));
+    }
+
+    #[test]
+    fn squarify_limited_continues_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut expected = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify(container, &mut expected[..], |&(_, n, _)| n, mkset_rect());
+
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        let (rest, rect_rest) =
+            squarify_limited(container, &mut slice[..], 2, |&(_, n, _)| n, mkset_rect());
+        assert_eq!(rest, 4..7);
+        assert_eq!(rect_rest, Rect { x: 3., y: 2.3333333, w: 3., h: 1.6666667 });
+        assert_eq!(slice[..4], expected[..4]);
+        squarify(rect_rest, &mut slice[rest], |&(_, n, _)| n, |(_, _, item_r), r| *item_r = r);
+        for (&(_, _, r), &(_, _, e)) in slice.iter().zip(&expected) {
+            assert!(displacement(&r, &e) < EPSILON, "{:?} != {:?}", r, e);
+        }
+    }
+
+    #[test]
+    fn squarify_sorted_by_category_f32() {
+        // (category, size)
+        let items = [
+            (0, 9.),
+            (1, 8.),
+            (2, 8.),
+            (0, 7.),
+            (1, 6.),
+            (2, 6.),
+            (0, 5.),
+            (1, 5.),
+            (2, 4.),
+            (0, 4.),
+            (1, 3.),
+            (2, 3.),
+            (0, 2.),
+            (1, 2.),
+            (2, 1.),
+            (0, 1.),
+        ];
+        let adjacent_pairs = |items: &[(u8, f32, Rect<f32>)]| {
+            let mut count = 0;
+            for (i, &(ca, _, a)) in items.iter().enumerate() {
+                for &(cb, _, b) in &items[i + 1..] {
+                    let a_grown = Rect {
+                        x: a.x - EPSILON,
+                        y: a.y - EPSILON,
+                        w: a.w + 2. * EPSILON,
+                        h: a.h + 2. * EPSILON,
+                    };
+                    if ca == cb && a_grown.intersection(&b).is_some() {
+                        count += 1;
+                    }
+                }
+            }
+            count
+        };
+        let container = Rect { x: 0., y: 0., w: 8., h: 10. };
+        let mut by_size: Vec<(u8, f32, Rect<f32>)> =
+            items.iter().map(|&(c, n)| (c, n, Rect { x: 0., y: 0., w: 0., h: 0. })).collect();
+        let mut by_category = by_size.clone();
+        sort_desc_by_size(&mut by_size[..], |&(_, n, _)| n);
+        squarify(container, &mut by_size[..], |&(_, n, _)| n, |(_, _, item_r), r| *item_r = r);
+        // Size buckets of 4, then category
+        squarify_sorted_by(
+            container,
+            &mut by_category[..],
+            |&(ca, na, _), &(cb, nb, _)| {
+                let (ba, bb) = ((na / 4.).ceil() as u8, (nb / 4.).ceil() as u8);
+                bb.cmp(&ba).then(ca.cmp(&cb)).then(nb.partial_cmp(&na).unwrap())
+            },
+            |&(_, n, _)| n,
+            |(_, _, item_r), r| *item_r = r,
+        );
+        let (a, b) = (adjacent_pairs(&by_category), adjacent_pairs(&by_size));
+        assert!(a > b, "{} <= {}", a, b);
+    }
+
+    #[test]
+    fn rect_try_flip_f32() {
+        let mut r = Rect { x: 1., y: 0.5, w: 2., h: 1. };
+        assert_eq!(r.try_flip_h(6.), Ok(()));
+        assert_eq!(r, Rect { x: 3., y: 0.5, w: 2., h: 1. });
+        assert_eq!(r.try_flip_v(4.), Ok(()));
+        assert_eq!(r, Rect { x: 3., y: 2.5, w: 2., h: 1. });
+
+        let mut r = Rect { x: 5., y: 3.5, w: 2., h: 1. };
+        assert_eq!(r.try_flip_h(6.), Err(FlipError));
+        assert_eq!(r.try_flip_v(4.), Err(FlipError));
+        assert_eq!(r, Rect { x: 5., y: 3.5, w: 2., h: 1. });
+        assert_eq!(Rect { x: -1., y: 0., w: 2., h: 1. }.try_flip_h(6.), Err(FlipError));
+    }
+
+    #[test]
+    fn aspect_histogram_buckets_f32() {
+        let rects = [
+            Rect { x: 0., y: 0., w: 1., h: 1. },
+            Rect { x: 0., y: 0., w: 2., h: 1. },
+            Rect { x: 0., y: 0., w: 1., h: 2.5 },
+            Rect { x: 0., y: 0., w: 4., h: 1. },
+            Rect { x: 0., y: 0., w: 5., h: 1. },
+        ];
+        // 4 bins of width 1: [1, 2), [2, 3), [3, 4), [4, 5]
+        assert_eq!(aspect_histogram(&rects, |&r| r, 4), [1, 2, 0, 2]);
+        assert_eq!(aspect_histogram(&rects[..1], |&r| r, 3), [1, 0, 0]);
+    }
+
+    #[test]
+    fn fill_fit_remainder_filler_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        for &axis in &[Axis::Vertical, Axis::Horizontal] {
+            let mut slice = mkslice::<f32>(&[6., 4., 2.]);
+            let mut filler = None;
+            fill_fit_remainder(
+                container,
+                &mut slice[..],
+                axis,
+                |&(_, n, _)| n,
+                mkset_rect11(EPSILON),
+                |r| filler = Some(r),
+            );
+            let filler = filler.unwrap();
+            assert!((filler.w * filler.h - 12.).abs() < EPSILON, "{:?}", filler);
+            let last = slice[2].2;
+            match axis {
+                Axis::Vertical => assert_eq!(last.y + last.h, filler.y),
+                Axis::Horizontal => assert_eq!(last.x + last.w, filler.x),
+            }
+            let mut rects: Vec<Rect<f32>> = slice.iter().map(|&(_, _, r)| r).collect();
+            rects.push(filler);
+            validate_coverage(container, &rects, |&r| r, EPSILON).unwrap();
+        }
+
+        let mut slice = mkslice::<f32>(&[12., 12.]);
+        let leftover = slice_fit(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        assert_eq!(leftover.h, 0.);
+        fill_fit_remainder(
+            container,
+            &mut slice[..],
+            Axis::Horizontal,
+            |&(_, n, _)| n,
+            mkset_rect(),
+            |_| panic!("no filler expected"),
+        );
+    }
+
+    #[test]
+    fn squarify_clamp_non_negative_f32() {
+        let container = Rect { x: 0., y: 0., w: 78.14286, h: 145.33333 };
+        let sizes = [32.333332, 12.428572, 0.];
+        let mut slice = mkslice::<f32>(&sizes);
+        squarify(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        assert!(slice[2].2.w < 0., "{:?}", slice[2].2);
+
+        let mut slice = mkslice::<f32>(&sizes);
+        let options = SquarifyOptions::new().clamp_non_negative(true);
+        squarify_with(container, &mut slice[..], &options, |&(_, n, _)| n, mkset_rect());
+        assert_eq!(slice[2].2.w, 0.);
+        assert!(slice[2].2.w.is_sign_positive());
+    }
+
+    #[test]
+    fn best_fit_selects_algorithm_f32() {
+        let sizes = [6., 6., 4., 3., 2., 2., 1.];
+        let container = Rect { x: 0., y: 0., w: 4., h: 4. };
+        let mut slice = mkslice::<f32>(&sizes);
+        let algorithm =
+            best_fit(container, &mut slice[..], &Algorithm::ALL, |&(_, n, _)| n, mkset_rect());
+        assert_eq!(algorithm, Algorithm::Binary);
+        let mut expected = mkslice::<f32>(&sizes);
+        binary(container, &mut expected[..], |&(_, n, _)| n, mkset_rect());
+        assert_eq!(slice, expected);
+
+        let algorithm = best_fit(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut slice[..],
+            &Algorithm::ALL,
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        assert_eq!(algorithm, Algorithm::Squarify);
+    }
+
+    #[test]
+    fn center_in_equal_margins_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify_with_margins(
+            container,
+            (0.5, 1., 0.25, 0.),
+            &mut slice[..],
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        let packed = bounding_box(&slice, |&(_, _, r)| r).unwrap();
+        assert_eq!((packed.x, packed.y), (0., 0.5));
+
+        center_in(container, &mut slice[..], |&(_, _, r)| r, mkset_rect());
+        let b = bounding_box(&slice, |&(_, _, r)| r).unwrap();
+        assert_eq!((b.w, b.h), (packed.w, packed.h));
+        assert!((b.x - (container.w - b.x - b.w)).abs() < EPSILON, "{:?}", b);
+        assert!((b.y - (container.h - b.y - b.h)).abs() < EPSILON, "{:?}", b);
+        assert_eq!(bounding_box(&[] as &[Rect<f32>], |&r| r), None);
+    }
+
+    #[test]
+    fn legend_layout_rows_f32() {
+        let legend = Rect { x: 10., y: 0., w: 5., h: 4. };
+        let rows = legend_layout(legend, 4, 0.5);
+        assert_eq!(rows.len(), 4);
+        let mut y = legend.y;
+        for &(swatch, label) in &rows {
+            assert_eq!((swatch.w, swatch.h), (0.5, 0.5));
+            assert_eq!((swatch.x, swatch.y), (10., y + 0.25));
+            assert_eq!(label, Rect { x: 10.5, y, w: 4.5, h: 1. });
+            y += 1.;
+        }
+        let tiles: Vec<Rect<f32>> =
+            rows.iter().map(|&(_, label)| Rect { x: 10., w: 5., ..label }).collect();
+        validate_coverage(legend, &tiles, |&r| r, EPSILON).unwrap();
+        assert!(legend_layout(legend, 0, 0.5).is_empty());
+    }
+
+    #[test]
+    fn relative_sizes_fractions_f32() {
+        let sizes = [6., 3., 1.5, 0.];
+        assert_eq!(relative_sizes(&sizes, |&n| n), [1., 0.5, 0.25, 0.]);
+        assert_eq!(relative_sizes(&[0., 0.], |&n| n), [0., 0.]);
+    }
+
+    #[test]
+    fn squarify_equal_areas_f32() {
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify_equal(Rect { x: 0., y: 0., w: 6., h: 4. }, &mut slice[..], mkset_rect());
+        for &(_, _, r) in &slice {
+            assert!((r.w * r.h - 24. / 7.).abs() < EPSILON * 10., "{:?}", r);
+        }
+        validate_coverage(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &slice,
+            |&(_, _, r)| r,
+            EPSILON * 10.,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn grid_weighted_rows_f32() {
+        let mut slice = mkslice::<f32>(&[1., 2., 3., 2., 2., 4.]);
+        grid_weighted(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut slice[..],
+            3,
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        let widths: Vec<f32> = slice.iter().map(|&(_, _, r)| r.w).collect();
+        assert_eq!(widths, [1., 2., 3., 1.5, 1.5, 3.]);
+        for (idx, &(_, _, r)) in slice.iter().enumerate() {
+            assert_eq!((r.y, r.h), (if idx < 3 { 0. } else { 2. }, 2.));
         }
-        writeln!(&mut f, "</svg>").unwrap();
+    }
 
-        f
+    #[test]
+    fn snap_to_grid_f32() {
+        let container = Rect { x: 0., y: 0., w: 160., h: 96. };
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        let before = slice.clone();
+        snap_to_grid(container, &mut slice[..], |&(_, _, r)| r, mkset_rect(), 8.);
+
+        for &(_, _, r) in &slice {
+            for v in [r.x, r.y, r.x + r.w, r.y + r.h] {
+                assert_eq!(v % 8., 0., "{:?} is not on the grid", r);
+            }
+            assert!(container.contains_rect(&r), "{:?}", r);
+        }
+        for (a, b) in before.iter().zip(&slice) {
+            for (c, d) in before.iter().zip(&slice) {
+                if a.2.x + a.2.w == c.2.x {
+                    assert_eq!(b.2.x + b.2.w, d.2.x, "{:?} and {:?} are no more flush", b, d);
+                }
+                if a.2.y + a.2.h == c.2.y {
+                    assert_eq!(b.2.y + b.2.h, d.2.y, "{:?} and {:?} are no more flush", b, d);
+                }
+            }
+        }
+        validate_coverage(container, &slice[..], |&(_, _, r)| r, 0.).unwrap();
+
+        // Container edges that aren't on the grid are kept
+        let container = Rect { x: 2., y: 0., w: 61., h: 43. };
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify(container, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        snap_to_grid(container, &mut slice[..], |&(_, _, r)| r, mkset_rect(), 8.);
+        for &(_, _, r) in &slice {
+            for v in [r.x, r.x + r.w] {
+                assert!(v % 8. == 0. || v == 2. || v == 63., "{:?} is not on the grid", r);
+            }
+            for v in [r.y, r.y + r.h] {
+                assert!(v % 8. == 0. || v == 43., "{:?} is not on the grid", r);
+            }
+            assert!(container.contains_rect(&r), "{:?}", r);
+        }
+        validate_coverage(container, &slice[..], |&(_, _, r)| r, 0.).unwrap();
     }
 
-    fn mkslice<N: Copy + Zero>(slice: &[N]) -> Vec<(usize, N, Rect<N>)> {
-        slice
-            .iter()
-            .copied()
-            .enumerate()
-            .map(|(i, n)| (i, n, Rect::from_size(N::zero(), N::zero())))
-            .collect()
+    #[test]
+    fn classify_changes_kinds_f32() {
+        let old = [
+            ("a", Rect { x: 0., y: 0., w: 3., h: 2. }),
+            ("b", Rect { x: 3., y: 0., w: 3., h: 2. }),
+            ("c", Rect { x: 0., y: 2., w: 6., h: 2. }),
+            ("d", Rect { x: 0., y: 4., w: 6., h: 1. }),
+        ];
+        let new = [
+            ("a", Rect { x: 0., y: 0., w: 3., h: 2. }),
+            ("b", Rect { x: 3., y: 0., w: 3., h: 3. }),
+            ("c", Rect { x: 0., y: 3., w: 6., h: 2. }),
+            ("e", Rect { x: 0., y: 5., w: 6., h: 1. }),
+        ];
+        assert_eq!(
+            classify_changes(&old, &new, |&(k, _)| k, |&(_, r)| r),
+            [Change::Resized("b"), Change::Moved("c"), Change::Added("e"), Change::Removed("d")]
+        );
     }
 
-    const EPSILON: f32 = 0.000001;
-    fn mkset_rect11<N>(epsilon: N) -> impl FnMut(&mut (usize, N, Rect<N>), Rect<N>)
-    where
-        N: NumOps + Signed + PartialOrd + Copy + Debug,
-    {
-        let mut idx = 0;
-        move |(i, n, item_r), r| {
-            assert_eq!(*i, idx, "f_item_set_rect must be called in stable order");
-            let d = (r.w * r.h - *n).abs();
-            assert!(
-                d <= epsilon,
-                "item rect size must match size, n = {:?}, r = {:?}, d = {:?}",
-                *n,
-                r,
-                d,
-            );
-            *item_r = r;
-            idx += 1;
+    #[test]
+    fn squarify_inscribed_square_f32() {
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify_inscribed(
+            Rect { x: 1., y: 2., w: 12., h: 4. },
+            &mut slice[..],
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+        let square = Rect { x: 5., y: 2., w: 4., h: 4. };
+        for &(_, _, r) in &slice {
+            assert!(square.contains_rect(&r), "{:?}", r);
         }
+        validate_coverage(square, &slice, |&(_, _, r)| r, EPSILON).unwrap();
     }
 
-    fn mkset_rect<N: Mul<Output = N> + PartialOrd + Copy + Debug>(
-    ) -> impl FnMut(&mut (usize, N, Rect<N>), Rect<N>) {
+    #[test]
+    fn squarify_with_borders_edges_f32() {
+        let container = Rect { x: 0., y: 0., w: 3., h: 3. };
+        let mut slice: Vec<(usize, f32, Rect<f32>, BorderEdges)> = (0..9)
+            .map(|i| (i, 1., Rect { x: 0., y: 0., w: 0., h: 0. }, BorderEdges::default()))
+            .collect();
+        squarify_with_borders(
+            container,
+            &mut slice[..],
+            |&(_, n, _, _)| n,
+            |(_, _, item_r, item_e), r, e| {
+                *item_r = r;
+                *item_e = e;
+            },
+        );
+        let near = |a: f32, b: f32| (a - b).abs() < EPSILON;
+        for &(i, _, r, e) in &slice {
+            let expected = BorderEdges {
+                top: near(r.y, 0.),
+                right: near(r.x + r.w, 3.),
+                bottom: near(r.y + r.h, 3.),
+                left: near(r.x, 0.),
+            };
+            assert_eq!(e, expected, "{} {:?}", i, r);
+            let count = [e.top, e.right, e.bottom, e.left].iter().filter(|&&b| b).count();
+            let is_corner =
+                (near(r.x, 0.) || near(r.x + r.w, 3.)) && (near(r.y, 0.) || near(r.y + r.h, 3.));
+            if is_corner {
+                assert_eq!(count, 2, "{} {:?}", i, r);
+            }
+        }
+        let center = slice.iter().find(|&&(_, _, r, _)| near(r.x, 1.) && near(r.y, 1.)).unwrap();
+        assert_eq!(center.3, BorderEdges::default());
+    }
+
+    #[test]
+    fn squarify_with_placeholders_reported_f32() {
+        let mut expected = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut expected[..],
+            |&(_, n, _)| n,
+            mkset_rect(),
+        );
+
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        let mut placeholders = Vec::new();
+        squarify_with_placeholders(
+            Rect { x: 0., y: 0., w: 6., h: 4. },
+            &mut slice[..],
+            |&(_, n, _)| n,
+            |&(i, _, _)| i == 3,
+            |(_, _, item_r), r| *item_r = r,
+            |(i, _, _), r| placeholders.push((*i, r)),
+        );
+        assert_eq!(placeholders, [(3, expected[3].2)]);
+        let r = placeholders[0].1;
+        assert!((r.w * r.h - 3.).abs() < EPSILON);
+        for (i, (&(_, _, r), &(_, _, e))) in slice.iter().zip(&expected).enumerate() {
+            if i != 3 {
+                assert_eq!(r, e);
+            }
+        }
+        assert_eq!(slice[3].2, Rect { x: 0., y: 0., w: 0., h: 0. });
+    }
+
+    #[test]
+    fn auto_selection_f32() {
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        assert_eq!(
+            auto(Rect { x: 0., y: 0., w: 6., h: 4. }, &mut slice[..], |&(_, n, _)| n, mkset_rect()),
+            Algorithm::Squarify
+        );
+
+        let mut slice = mkslice::<f32>(&[1., 6., 4., 3., 2., 2., 6.]);
+        assert_eq!(
+            auto(Rect { x: 0., y: 0., w: 6., h: 4. }, &mut slice[..], |&(_, n, _)| n, mkset_rect()),
+            Algorithm::Binary
+        );
+
+        let mut slice = mkslice::<f32>(&[1., 3.]);
+        assert_eq!(
+            auto(Rect { x: 0., y: 0., w: 6., h: 4. }, &mut slice[..], |&(_, n, _)| n, mkset_rect()),
+            Algorithm::Dice
+        );
+        assert_eq!(slice[1].2, Rect { x: 1.5, y: 0., w: 4.5, h: 4. });
+        assert_eq!(
+            auto(
+                Rect { x: 0., y: 0., w: 4., h: 6. },
+                &mut slice[..1],
+                |&(_, n, _)| n,
+                mkset_rect()
+            ),
+            Algorithm::Slice
+        );
+        assert_eq!(slice[0].2, Rect { x: 0., y: 0., w: 4., h: 6. });
+    }
+
+    #[test]
+    fn sunburst_two_levels_f32() {
+        let mut tree = mktree(&[&[6., 6.], &[4., 3.], &[2., 2., 1.]]);
+        let full = 2. * std::f32::consts::PI;
+        let mut arcs = Vec::new();
+        sunburst(
+            (0., full),
+            10.,
+            &mut tree[..],
+            |n| n.size,
+            |n| &mut n.children[..],
+            |n, depth, arc| arcs.push((n.size, depth, arc)),
+        );
+        assert_eq!(arcs.len(), 10);
+        assert_eq!(arcs[0].2.start_angle, 0.);
         let mut idx = 0;
-        move |(i, _n, item_r), r| {
-            assert_eq!(*i, idx, "f_item_set_rect must be called in stable order");
-            *item_r = r;
-            idx += 1;
+        let mut end = 0.;
+        for parent in &tree {
+            let (size, depth, arc) = arcs[idx];
+            assert_eq!((size, depth), (parent.size, 0));
+            assert_eq!((arc.inner_radius, arc.outer_radius), (0., 10.));
+            assert!((arc.end_angle - arc.start_angle - full * size / 24.).abs() < EPSILON);
+            let children = &arcs[idx + 1..idx + 1 + parent.children.len()];
+            let span: f32 = children.iter().map(|(_, _, a)| a.end_angle - a.start_angle).sum();
+            assert!((span - (arc.end_angle - arc.start_angle)).abs() < EPSILON);
+            assert_eq!(children[0].2.start_angle, arc.start_angle);
+            for &(_, depth, child) in children {
+                assert_eq!(depth, 1);
+                assert_eq!((child.inner_radius, child.outer_radius), (10., 20.));
+            }
+            idx += 1 + parent.children.len();
+            end = arc.end_angle;
+        }
+        assert_eq!(end, full);
+    }
+
+    #[test]
+    fn suggest_container_area_f32() {
+        let sizes = [6., 6., 4., 3., 2., 2., 1., 4., 8.];
+        let (w, h) = suggest_container(&sizes, |&n| n, 1.);
+        assert!((w * h - 36.).abs() < EPSILON * 10.);
+        assert!((w / h - 1.).abs() < EPSILON * 10., "3⨯3 square tiles make a square");
+
+        // 2 columns of 4 tiles of aspect 2
+        let (w, h) = suggest_container(&sizes[..8], |&n| n, 2.);
+        assert!((w * h - 28.).abs() < EPSILON * 10.);
+        assert!((w / h - 1.).abs() < EPSILON * 10.);
+
+        // 3 wide tiles in a column
+        let (w, h) = suggest_container(&sizes[..3], |&n| n, 4.);
+        assert!((w * h - 16.).abs() < EPSILON * 10.);
+        assert!((w / h - 4. / 3.).abs() < EPSILON * 10.);
+        assert_eq!(suggest_container(&[] as &[f32], |&n| n, 1.), (0., 0.));
+    }
+
+    #[test]
+    fn squarify_avoiding_hole_f32() {
+        let rect = Rect { x: 0., y: 0., w: 10., h: 10. };
+        let hole = Rect { x: 3., y: 3., w: 4., h: 4. };
+        let mut items = mkslice::<f32>(&[20., 16., 12., 10., 8., 8., 6., 4.]);
+        squarify_avoiding(rect, &mut items, &[hole], |&(_, n, _)| n, mkset_rect());
+        assert_tiling(rect, &items, &[hole], 84.);
+    }
+
+    #[test]
+    fn binary_tree_leaves_f32() {
+        fn check(node: &BinaryNode<f32>, items: &[(usize, f32, Rect<f32>)], count: &mut usize) {
+            match node {
+                BinaryNode::Leaf { index, rect } => {
+                    assert_eq!(*index, *count);
+                    assert_eq!(*rect, items[*index].2);
+                    *count += 1;
+                }
+                BinaryNode::Split { rect, left, right } => {
+                    assert!(rect.contains_rect(&left.rect()), "{:?} {:?}", rect, left);
+                    assert!(rect.contains_rect(&right.rect()), "{:?} {:?}", rect, right);
+                    check(left, items, count);
+                    check(right, items, count);
+                }
+            }
+        }
+
+        let rect = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let sizes = [6., 6., 4., 3., 2., 2., 1.];
+        let mut items = mkslice::<f32>(&sizes);
+        binary(rect, &mut items, |&(_, n, _)| n, mkset_rect11(EPSILON));
+        let tree = binary_tree(rect, &items, |&(_, n, _)| n);
+        assert_eq!(tree.rect(), rect);
+        let mut count = 0;
+        check(&tree, &items, &mut count);
+        assert_eq!(count, sizes.len());
+
+        let mut items = mkslice::<f32>(&[0., 0., 0.]);
+        binary(rect, &mut items, |&(_, n, _)| n, mkset_rect());
+        let mut count = 0;
+        check(&binary_tree(rect, &items, |&(_, n, _)| n), &items, &mut count);
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn squarify_constrained_max_per_strip_f32() {
+        // Longest run of consecutive tiles stacked in the same column or row
+        fn longest_strip(items: &[(usize, f32, Rect<f32>)]) -> usize {
+            let (mut longest, mut run) = (1, 1);
+            for pair in items.windows(2) {
+                let (a, b) = (pair[0].2, pair[1].2);
+                let same_column = (a.x - b.x).abs() < EPSILON && (a.w - b.w).abs() < EPSILON;
+                let same_row = (a.y - b.y).abs() < EPSILON && (a.h - b.h).abs() < EPSILON;
+                run = if same_column || same_row { run + 1 } else { 1 };
+                longest = longest.max(run);
+            }
+            longest
+        }
+
+        let rect = Rect { x: 0., y: 0., w: 5., h: 4. };
+        let sizes = [1.; 20];
+        let mut items = mkslice::<f32>(&sizes);
+        squarify(rect, &mut items, |&(_, n, _)| n, mkset_rect11(EPSILON));
+        assert!(longest_strip(&items) > 3);
+
+        let mut unconstrained = mkslice::<f32>(&sizes);
+        squarify_constrained(rect, &mut unconstrained, 1, usize::MAX, |&(_, n, _)| n, mkset_rect());
+        assert_eq!(unconstrained, items);
+
+        let mut items = mkslice::<f32>(&sizes);
+        squarify_constrained(rect, &mut items, 1, 3, |&(_, n, _)| n, mkset_rect11(EPSILON));
+        assert!(longest_strip(&items) <= 3, "{:?}", items);
+    }
+
+    #[test]
+    fn painters_order_down_right_f32() {
+        let mut items = mkslice::<f32>(&[1., 1., 1., 1.]);
+        let rect = Rect { x: 0., y: 0., w: 2., h: 2. };
+        for ((_, _, r), q) in items.iter_mut().zip(rect.quadrants().iter()) {
+            *r = *q;
+        }
+        let order = painters_order(&items, |&(_, _, r)| r, (1., 1.));
+        assert_eq!(order[0], 0, "top left tile is drawn first");
+        assert_eq!(order[3], 3, "bottom right tile is drawn last");
+        assert_eq!(painters_order(&items, |&(_, _, r)| r, (-1., -1.))[0], 3);
+    }
+
+    #[test]
+    fn binary_with_stats_depth_f32() {
+        let mut items = mkslice::<f32>(&[1.; 7]);
+        let stats = binary_with_stats(
+            Rect { x: 0., y: 0., w: 7., h: 1. },
+            &mut items,
+            |&(_, n, _)| n,
+            mkset_rect11(EPSILON),
+        );
+        // ceil(log2(7)) levels, pairs of leaves are set without recursing
+        assert_eq!(stats, LayoutStats { max_depth: 3, calls: 6 });
+
+        let rect = Rect { x: 0., y: 0., w: 6., h: 4. };
+
+        let mut items = mkslice::<f32>(&[12., 12.]);
+        let stats = binary_with_stats(rect, &mut items, |&(_, n, _)| n, mkset_rect11(EPSILON));
+        assert_eq!(stats, LayoutStats { max_depth: 1, calls: 1 }, "only the top-level call");
+
+        let mut items = mkslice::<f32>(&[24.]);
+        let stats = binary_with_stats(rect, &mut items, |&(_, n, _)| n, mkset_rect11(EPSILON));
+        assert_eq!(stats, LayoutStats::default(), "a single item doesn't recurse");
+        let stats = binary_with_stats(rect, &mut [] as &mut [f32], |&n| n, |_, _| {});
+        assert_eq!(stats, LayoutStats::default());
+
+        let mut items = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        let stats = ordered_pivot_by_middle_with_stats(
+            rect,
+            &mut items,
+            |&(_, n, _)| n,
+            mkset_rect11(EPSILON),
+        );
+        assert_eq!(stats.max_depth, 2, "{:?}", stats);
+    }
+
+    #[test]
+    fn lod_squarify_culling_f32() {
+        let rect = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let sizes = [12., 6., 3., 1., 1., 0.5, 0.5];
+        let culled = |zoom: f32| {
+            let mut items = mkslice::<f32>(&sizes);
+            lod_squarify(rect, &mut items, zoom, 4., |&(_, n, _)| n, mkset_rect());
+            assert_tiling(rect, &items, &[], 24.);
+            items.iter().filter(|(_, _, r)| r.w * r.h == 0.).count()
+        };
+        assert_eq!(culled(1.), 5, "tiles under 4 units are culled");
+        assert_eq!(culled(0.1), 6, "only the largest tile is left");
+        assert_eq!(culled(3.), 0, "all tiles appear");
+    }
+
+    #[test]
+    fn rect_edges_i32() {
+        let r = Rect { x: 1, y: 2, w: 6, h: 4 };
+        assert_eq!((r.min_x(), r.min_y()), (1, 2));
+        assert_eq!((r.max_x(), r.max_y()), (7, 6));
+    }
+
+    #[test]
+    fn pad_by_aspect_thin_tiles_f32() {
+        let mut items = mkslice::<f32>(&[0., 0., 0., 0.]);
+        items[0].2 = Rect { x: 0., y: 0., w: 10., h: 10. };
+        items[1].2 = Rect { x: 10., y: 0., w: 10., h: 2. };
+        items[2].2 = Rect { x: 10., y: 2., w: 40., h: 1. };
+        items[3].2 = Rect { x: 0., y: 10., w: 0., h: 5. };
+        pad_by_aspect(&mut items, |&(_, _, r)| r, mkset_rect(), 0.1, 0.4);
+
+        let r = items[0].2;
+        assert!((r.x - 0.1).abs() < EPSILON && (r.w - 9.8).abs() < EPSILON, "base padding {:?}", r);
+        let r = items[1].2;
+        assert!((r.x - 10.4).abs() < EPSILON && (r.h - 1.2).abs() < EPSILON, "{:?}", r);
+        let r = items[2].2;
+        assert!((r.y - 2.4).abs() < EPSILON && (r.h - 0.2).abs() < EPSILON, "capped {:?}", r);
+        assert_eq!(items[3].2, Rect { x: 0., y: 10., w: 0., h: 5. });
+
+        items[0].2 = Rect { x: 0., y: 0., w: 0.5, h: 0.2 };
+        pad_by_aspect(&mut items[..1], |&(_, _, r)| r, mkset_rect(), 0.1, 1.);
+        assert!(items[0].2.w >= 0. && items[0].2.h >= 0., "{:?}", items[0].2);
+    }
+
+    #[test]
+    fn squarify_with_gutter_budget_area_f32() {
+        let rect = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut items = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify_with_gutter_budget(rect, &mut items, 0.05, |&(_, n, _)| n, mkset_rect());
+        assert_tiling(rect, &items, &[], 0.95 * 24.);
+    }
+
+    #[test]
+    fn single_item_fills_container_f32() {
+        let rect = Rect { x: 0.1, y: 0.7, w: 3.3, h: 1.9 };
+        for algorithm in Algorithm::ALL {
+            let mut items = mkslice::<f32>(&[7.77]);
+            algorithm.layout(rect, &mut items, |&(_, n, _)| n, mkset_rect());
+            assert_eq!(items[0].2, rect, "{:?}", algorithm);
+
+            let mut items = mkslice::<f32>(&[0.]);
+            algorithm.layout(rect, &mut items, |&(_, n, _)| n, mkset_rect());
+            assert_eq!(items[0].2, Rect { w: 0., h: 0., ..rect }, "{:?}", algorithm);
+        }
+        let mut items = mkslice::<f32>(&[7.77]);
+        squarify_with(rect, &mut items, &SquarifyOptions::new(), |&(_, n, _)| n, mkset_rect());
+        assert_eq!(items[0].2, rect);
+
+        type Item = (usize, f32, Rect<f32>);
+        type Variant<'a> = &'a dyn Fn(&mut [Item]);
+        let size = |&(_, n, _): &Item| n;
+        let variants: [(&str, Variant); 9] = [
+            ("stable", &|items| squarify_stable(rect, items, &[], size, mkset_rect())),
+            ("with_aspect", &|items| {
+                squarify_with_aspect(rect, items, size, |_| Some(2.), mkset_rect())
+            }),
+            ("constrained", &|items| squarify_constrained(rect, items, 2, 3, size, mkset_rect())),
+            ("container_aspect", &|items| {
+                squarify_container_aspect(rect, items, size, mkset_rect())
+            }),
+            ("first_strip", &|items| squarify_first_strip(rect, items, 1, size, mkset_rect())),
+            ("min_variance", &|items| squarify_min_variance(rect, items, size, mkset_rect())),
+            ("reusing_strips", &|items| {
+                assert_eq!(squarify_reusing_strips(rect, items, &[], size, mkset_rect()), [1]);
+            }),
+            ("limited", &|items| {
+                squarify_limited(rect, items, 1, size, mkset_rect());
+            }),
+            ("with_borders", &|items| {
+                squarify_with_borders(rect, items, size, |item, r, edges| {
+                    assert_eq!(
+                        edges,
+                        BorderEdges { top: true, right: true, bottom: true, left: true }
+                    );
+                    item.2 = r;
+                })
+            }),
+        ];
+        for (name, variant) in variants.iter() {
+            let mut items = mkslice::<f32>(&[7.77]);
+            variant(&mut items);
+            assert_eq!(items[0].2, rect, "{}", name);
+
+            let mut items = mkslice::<f32>(&[0.]);
+            variant(&mut items);
+            assert_eq!(items[0].2, Rect { w: 0., h: 0., ..rect }, "{}", name);
+        }
+        assert_eq!(squarify_splits(rect, &[0.], |&n| n), [1]);
+        assert_eq!(squarify_splits(Rect { w: 0., ..rect }, &[6., 4.], |&n| n), [2]);
+    }
+
+    #[test]
+    fn squarify_btree_key_order_f32() {
+        let rect = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let map: BTreeMap<u32, f32> = [(2021, 2.), (2019, 6.), (2020, 4.), (2022, 12.)].into();
+        let tiles = squarify_btree(rect, map);
+        let keys: Vec<u32> = tiles.iter().map(|&(k, _)| k).collect();
+        assert_eq!(keys, [2019, 2020, 2021, 2022]);
+
+        let mut items = mkslice::<f32>(&[6., 4., 2., 12.]);
+        squarify(rect, &mut items, |&(_, n, _)| n, mkset_rect11(EPSILON));
+        for ((_, r), (_, _, expected)) in tiles.iter().zip(items.iter()) {
+            assert_eq!(r, expected);
+        }
+    }
+
+    #[test]
+    fn corner_radii_clamped_f32() {
+        assert_eq!(Rect { x: 1., y: 2., w: 6., h: 4. }.max_corner_radius(), 2.);
+        let mut items = mkslice::<f32>(&[0., 0.]);
+        items[0].2 = Rect { x: 0., y: 0., w: 10., h: 8. };
+        items[1].2 = Rect { x: 10., y: 0., w: 3., h: 1. };
+        assert_eq!(corner_radii(&items, |&(_, _, r)| r, 2.), [2., 0.5]);
+    }
+
+    #[test]
+    fn update_one_matches_binary_f32() {
+        let cases = [
+            (
+                Rect { x: 0., y: 0., w: 16., h: 8. },
+                vec![32., 16., 16., 8., 8., 8., 8., 4., 4., 4., 4., 4., 4., 4., 4.],
+                vec![(14, 3.), (9, 2.), (0, 36.), (3, 8.)],
+            ),
+            // The right subtree aspect ratio flips, changing its split orientation
+            (
+                Rect { x: 0., y: 0., w: 10., h: 10. },
+                vec![50., 10., 10., 10., 10., 10.],
+                vec![(0, 150.)],
+            ),
+            // The changed item fills the whole rect
+            (Rect { x: 0., y: 0., w: 10., h: 10. }, vec![100., 0., 0.], vec![(0, 30.), (1, 20.)]),
+        ];
+        for (rect, sizes, changes) in cases {
+            let mut items = mkslice::<f32>(&sizes);
+            binary(rect, &mut items, |&(_, n, _)| n, mkset_rect11(EPSILON));
+            let old_layout: Vec<Rect<f32>> = items.iter().map(|&(_, _, r)| r).collect();
+
+            for (changed_index, size) in changes {
+                let mut expected = mkslice::<f32>(&sizes);
+                expected[changed_index].1 = size;
+                let mut items = expected.clone();
+                binary(rect, &mut expected, |&(_, n, _)| n, mkset_rect());
+                update_one(
+                    rect,
+                    &mut items,
+                    changed_index,
+                    &old_layout,
+                    |&(_, n, _)| n,
+                    mkset_rect(),
+                );
+                for ((_, _, r), (_, _, expected_r)) in items.iter().zip(expected.iter()) {
+                    assert!(
+                        displacement(r, expected_r) < EPSILON,
+                        "{} {:?} {:?}",
+                        changed_index,
+                        r,
+                        expected_r
+                    );
+                }
+            }
         }
     }
 
     #[test]
-    fn binary_f32() {
-        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
-        binary(
-            Rect { x: 0., y: 0., w: 6., h: 4. },
-            &mut slice[..],
-            |&(_, n, _)| n,
-            mkset_rect11(EPSILON),
-        );
-        assert_eq!(
-            slice,
-            [
-                (0, 6.0, Rect { x: 0.0, y: 0.0, w: 3.0, h: 2.0 }),
-                (1, 6.0, Rect { x: 0.0, y: 2.0, w: 3.0, h: 2.0 }),
-                (2, 4.0, Rect { x: 3.0, y: 0.0, w: 3.0, h: 1.3333334 }),
-                (3, 3.0, Rect { x: 3.0, y: 1.3333334, w: 1.125, h: 2.6666665 }),
-                (4, 2.0, Rect { x: 4.125, y: 1.3333334, w: 1.875, h: 1.0666667 }),
-                (5, 2.0, Rect { x: 4.125, y: 2.4, w: 1.25, h: 1.5999999 }),
-                (6, 1.0, Rect { x: 5.375, y: 2.4, w: 0.625, h: 1.5999999 })
-            ]
-        );
-        eprintln!(
-            "<!-- binary -->\n{}",
-            svg(Rect { x: 0., y: 0., w: 6., h: 4. }, &slice[..], 50.0)
-        );
+    fn layout_bytes_round_trip_f32() {
+        let rect = Rect { x: 0.5, y: -1.25, w: 100., h: 30. };
+        let sizes: Vec<f32> = (1..=100).rev().map(|n| n as f32 * 0.37).collect();
+        let mut items = mkslice::<f32>(&sizes);
+        squarify(rect, &mut items, |&(_, n, _)| n, mkset_rect());
+        let layout = Layout::from_items(rect, &items, |&(_, _, r)| r);
 
-        let mut slice = mkslice::<f32>(&[12., 12., 8., 6., 4., 4., 2.]);
-        binary(Rect { x: 0., y: 0., w: 6., h: 4. }, &mut slice[..], |&(_, n, _)| n, mkset_rect());
-        assert_eq!(
-            slice,
-            [
-                (0, 12.0, Rect { x: 0.0, y: 0.0, w: 3.0, h: 2.0 }),
-                (1, 12.0, Rect { x: 0.0, y: 2.0, w: 3.0, h: 2.0 }),
-                (2, 8.0, Rect { x: 3.0, y: 0.0, w: 3.0, h: 1.3333334 }),
-                (3, 6.0, Rect { x: 3.0, y: 1.3333334, w: 1.125, h: 2.6666665 }),
-                (4, 4.0, Rect { x: 4.125, y: 1.3333334, w: 1.875, h: 1.0666667 }),
-                (5, 4.0, Rect { x: 4.125, y: 2.4, w: 1.25, h: 1.5999999 }),
-                (6, 2.0, Rect { x: 5.375, y: 2.4, w: 0.625, h: 1.5999999 })
-            ]
-        );
+        let bytes = layout.to_bytes();
+        assert_eq!(bytes.len(), 5 + 101 * 16);
+        assert_eq!(Layout::<f32>::from_bytes(&bytes), Ok(layout.clone()));
+        assert_eq!(Layout::<f64>::from_bytes(&bytes), Err(DecodeError), "wrong scalar type");
+        assert_eq!(Layout::<f32>::from_bytes(&bytes[..bytes.len() - 1]), Err(DecodeError));
+        assert_eq!(Layout::<f32>::from_bytes(&[]), Err(DecodeError));
     }
 
     #[test]
-    fn squarify_paper_example_f32() {
-        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
-        _squarify(
-            Rect { x: 0., y: 0., w: 6., h: 4. },
-            &mut slice[..],
-            |&(_, n, _)| n,
-            mkset_rect11(EPSILON),
-        );
-        assert_eq!(
-            slice,
-            [
-                (0, 6.0, Rect { x: 0.0, y: 0.0, w: 3.0, h: 2.0 }),
-                (1, 6.0, Rect { x: 0.0, y: 2.0, w: 3.0, h: 2.0 }),
-                (2, 4.0, Rect { x: 3.0, y: 0.0, w: 1.7142857, h: 2.3333333 }),
-                (3, 3.0, Rect { x: 4.714286, y: 0.0, w: 1.2857141, h: 2.3333333 }),
-                (4, 2.0, Rect { x: 3.0, y: 2.3333333, w: 1.1999999, h: 1.6666667 }),
-                (5, 2.0, Rect { x: 4.2, y: 2.3333333, w: 1.1999999, h: 1.6666667 }),
-                (6, 1.0, Rect { x: 5.3999996, y: 2.3333333, w: 0.60000014, h: 1.6666667 })
-            ]
-        );
-        eprintln!(
-            "<!-- squarify -->\n{}",
-            svg(Rect { x: 0., y: 0., w: 6., h: 4. }, &slice[..], 50.0)
-        );
-        squarify(
-            Rect { x: 0., y: 0., w: 12., h: 8. },
-            &mut slice[..],
+    fn timeline_rows_f32() {
+        let rect = Rect { x: 0., y: 0., w: 10., h: 6. };
+        let mut items = mkslice::<f32>(&[3., 4., 2., 5., 1., 4., 3., 2., 6.]);
+        timeline(rect, &mut items, 2., |&(_, n, _)| n, mkset_rect());
+
+        let mut row_widths = [0.; 3];
+        for pair in items.windows(2) {
+            let (a, b) = (pair[0].2, pair[1].2);
+            assert!(b.y > a.y || (b.y == a.y && b.x >= a.max_x() - EPSILON), "{:?} {:?}", a, b);
+        }
+        for (_, n, r) in &items {
+            assert_eq!(r.h, 2.);
+            row_widths[(r.y / 2.) as usize] += r.w;
+            assert!(*n == 0. || r.w > 0.);
+        }
+        for w in row_widths.iter() {
+            assert!((w - 10.).abs() < EPSILON, "{:?}", row_widths);
+        }
+        assert_eq!(items[0].2.x, 0.);
+        assert_eq!(items[0].2.y, 0.);
+
+        // Rows wrap as they fill, not after a fixed share of the total
+        let mut items = mkslice::<f32>(&[3., 4., 2., 5., 1., 4., 3., 2., 6.]);
+        timeline(
+            Rect { x: 0., y: 0., w: 10., h: 3. },
+            &mut items,
+            2.,
             |&(_, n, _)| n,
             mkset_rect(),
         );
-        assert_eq!(
-            slice,
-            [
-                (0, 6.0, Rect { x: 0.0, y: 0.0, w: 6.0, h: 4.0 }),
-                (1, 6.0, Rect { x: 0.0, y: 4.0, w: 6.0, h: 4.0 }),
-                (2, 4.0, Rect { x: 6.0, y: 0.0, w: 3.4285715, h: 4.6666665 }),
-                (3, 3.0, Rect { x: 9.428572, y: 0.0, w: 2.5714283, h: 4.6666665 }),
-                (4, 2.0, Rect { x: 6.0, y: 4.6666665, w: 2.3999999, h: 3.3333335 }),
-                (5, 2.0, Rect { x: 8.4, y: 4.6666665, w: 2.3999999, h: 3.3333335 }),
-                (6, 1.0, Rect { x: 10.799999, y: 4.6666665, w: 1.2000003, h: 3.3333335 })
-            ]
-        );
-        squarify(
-            Rect { x: 1., y: 2., w: 12., h: 8. },
-            &mut slice[..],
+        let rows: Vec<f32> = items.iter().map(|(_, _, r)| r.y).collect();
+        assert_eq!(rows, [0., 0., 0., 0., 0., 0., 2., 2., 2.]);
+
+        let mut items = mkslice::<f32>(&[0., 0.]);
+        timeline(
+            Rect { x: 1., y: 2., w: 10., h: 6. },
+            &mut items,
+            2.,
             |&(_, n, _)| n,
             mkset_rect(),
         );
-        assert_eq!(
-            slice,
-            [
-                (0, 6.0, Rect { x: 1.0, y: 2.0, w: 6.0, h: 4.0 }),
-                (1, 6.0, Rect { x: 1.0, y: 6.0, w: 6.0, h: 4.0 }),
-                (2, 4.0, Rect { x: 7.0, y: 2.0, w: 3.4285715, h: 4.6666665 }),
-                (3, 3.0, Rect { x: 10.428572, y: 2.0, w: 2.5714283, h: 4.6666665 }),
-                (4, 2.0, Rect { x: 7.0, y: 6.6666665, w: 2.3999999, h: 3.3333335 }),
-                (5, 2.0, Rect { x: 9.4, y: 6.6666665, w: 2.3999999, h: 3.3333335 }),
-                (6, 1.0, Rect { x: 11.799999, y: 6.6666665, w: 1.2000003, h: 3.3333335 })
-            ]
-        );
+        for (_, _, r) in &items {
+            assert_eq!(*r, Rect { x: 1., y: 2., w: 0., h: 0. });
+        }
+        let mut items = mkslice::<f32>(&[]);
+        timeline(rect, &mut items, 2., |&(_, n, _)| n, mkset_rect());
     }
 
     #[test]
-    fn ordered_pivot_by_middle_f32() {
-        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
-        ordered_pivot_by_middle(
+    fn min_container_area_inverse_f32() {
+        let size = |&n: &f32| n;
+        // The smallest item is 1/24 of the total
+        assert_eq!(min_container_area(&[6., 6., 4., 3., 2., 2., 1.], size, 10.), 240.);
+        // Half the fraction, twice the area
+        assert_eq!(min_container_area(&[12., 12., 8., 6., 4., 4., 1., 1.], size, 10.), 480.);
+        assert_eq!(min_container_area(&[0., 4., 4.], size, 10.), 20.);
+        assert_eq!(min_container_area(&[0.], size, 10.), 0.);
+    }
+
+    #[test]
+    fn squarify_square_prefers_f32() {
+        let rect = Rect { x: 0., y: 0., w: 4., h: 4. };
+        let sizes = [6., 4., 3., 2., 1.];
+        let layout = |square_prefers| {
+            let mut items = mkslice::<f32>(&sizes);
+            let options = SquarifyOptions::new().square_prefers(square_prefers);
+            squarify_with(rect, &mut items, &options, |&(_, n, _)| n, mkset_rect11(EPSILON));
+            validate_coverage(rect, &items, |&(_, _, r)| r, EPSILON).unwrap();
+            items
+        };
+        let rows = layout(Axis::Horizontal);
+        let columns = layout(Axis::Vertical);
+        assert_ne!(rows, columns);
+        assert_eq!(rows[1].2.y, 0., "first strip is a row");
+        assert_eq!(columns[1].2.x, 0., "first strip is a column");
+
+        let mut items = mkslice::<f32>(&sizes);
+        squarify(rect, &mut items, |&(_, n, _)| n, mkset_rect());
+        assert_eq!(items, rows);
+    }
+
+    #[test]
+    fn columns_stacked_f32() {
+        let rect = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut items = mkslice::<f32>(&[6., 3., 3., 4., 4., 4.]);
+        columns(rect, &mut items, 2, |&(_, n, _)| n, mkset_rect());
+
+        let mut heights = [0.; 2];
+        for (idx, (_, _, r)) in items.iter().enumerate() {
+            assert_eq!(r.w, 3.);
+            assert_eq!(r.x, if idx < 3 { 0. } else { 3. });
+            heights[idx / 3] += r.h;
+        }
+        assert_eq!(heights, [4., 4.]);
+        assert_eq!(items[0].2.h, 2., "6 of a column of 12");
+        assert_eq!(items[1].2.h, 1.);
+        assert!((items[4].2.h - 4. / 3.).abs() < EPSILON);
+    }
+
+    #[test]
+    fn adjacency_paper_f32() {
+        let mut items = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify(
             Rect { x: 0., y: 0., w: 6., h: 4. },
-            &mut slice[..],
+            &mut items,
             |&(_, n, _)| n,
             mkset_rect11(EPSILON),
         );
-        eprintln!(
-            "<!-- ordered_pivot_by_middle -->\n{}",
-            svg(Rect { x: 0., y: 0., w: 6., h: 4. }, &slice[..], 50.0)
-        );
+        let graph = adjacency(&items, |&(_, _, r)| r, EPSILON);
         assert_eq!(
-            slice,
+            graph,
             [
-                (0, 6.0, Rect { x: 0.0, y: 0.0, w: 1.5, h: 4.0 }),
-                (1, 6.0, Rect { x: 1.5, y: 0.0, w: 2.5, h: 2.4 }),
-                (2, 4.0, Rect { x: 1.5, y: 2.4, w: 2.5, h: 1.5999999 }),
-                (3, 3.0, Rect { x: 4.0, y: 0.0, w: 1.25, h: 2.4 }),
-                (4, 2.0, Rect { x: 4.0, y: 2.4, w: 1.25, h: 1.5999999 }),
-                (5, 2.0, Rect { x: 5.25, y: 0.0, w: 0.75, h: 2.6666667 }),
-                (6, 1.0, Rect { x: 5.25, y: 2.6666667, w: 0.75, h: 1.3333333 })
+                vec![1, 2],
+                vec![0, 2, 4],
+                vec![0, 1, 3, 4, 5],
+                vec![2, 5, 6],
+                vec![1, 2, 5],
+                vec![2, 3, 4, 6],
+                vec![3, 5],
             ]
         );
 
-        let mut slice = mkslice::<f32>(&[12., 12., 8., 6., 4., 4., 2.]);
-        ordered_pivot_by_middle(
+        // Diagonal quadrants only touch by a corner
+        let quadrants = Rect { x: 0., y: 0., w: 2., h: 2. }.quadrants();
+        let graph = adjacency(&quadrants, |&r| r, EPSILON);
+        assert_eq!(graph[0], [1, 2]);
+        assert_eq!(graph[3], [1, 2]);
+    }
+
+    #[test]
+    fn squarify_fit_leftover_f32() {
+        let rect = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut items = mkslice::<f32>(&[6., 6., 4., 2., 2.]);
+        let leftover = squarify_fit(rect, &mut items, |&(_, n, _)| n, mkset_rect11(EPSILON));
+        assert!((leftover.w * leftover.h - 4.).abs() < EPSILON, "{:?}", leftover);
+        assert!(rect.contains_rect(&leftover));
+        for (_, _, r) in &items {
+            assert!(r.intersection(&leftover).is_none(), "{:?} {:?}", r, leftover);
+        }
+
+        let mut items = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        let leftover = squarify_fit(rect, &mut items, |&(_, n, _)| n, mkset_rect11(EPSILON));
+        assert!(leftover.w * leftover.h < EPSILON, "{:?}", leftover);
+    }
+
+    #[test]
+    fn reveal_order_modes_f32() {
+        let mut items = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        squarify(
             Rect { x: 0., y: 0., w: 6., h: 4. },
-            &mut slice[..],
+            &mut items,
             |&(_, n, _)| n,
-            mkset_rect(),
+            mkset_rect11(EPSILON),
         );
-        assert_eq!(
-            slice,
-            [
-                (0, 12.0, Rect { x: 0.0, y: 0.0, w: 1.5, h: 4.0 }),
-                (1, 12.0, Rect { x: 1.5, y: 0.0, w: 2.5, h: 2.4 }),
-                (2, 8.0, Rect { x: 1.5, y: 2.4, w: 2.5, h: 1.5999999 }),
-                (3, 6.0, Rect { x: 4.0, y: 0.0, w: 1.25, h: 2.4 }),
-                (4, 4.0, Rect { x: 4.0, y: 2.4, w: 1.25, h: 1.5999999 }),
-                (5, 4.0, Rect { x: 5.25, y: 0.0, w: 0.75, h: 2.6666667 }),
-                (6, 2.0, Rect { x: 5.25, y: 2.6666667, w: 0.75, h: 1.3333333 })
-            ]
+        let order = reveal_order(&items, |&(_, _, r)| r, RevealMode::Reading);
+        for pair in order.windows(2) {
+            let (a, b) = (items[pair[0]].2, items[pair[1]].2);
+            assert!(a.y < b.y || (a.y == b.y && a.x <= b.x), "{:?} {:?}", a, b);
+        }
+        assert_eq!(order, [0, 2, 3, 1, 4, 5, 6]);
+
+        let order = reveal_order(&items, |&(_, _, r)| r, RevealMode::LargestFirst);
+        assert_eq!(order, [0, 1, 2, 3, 4, 5, 6]);
+        let order = reveal_order(&items, |&(_, _, r)| r, RevealMode::SpiralOut);
+        assert_eq!(order.len(), 7);
+        assert_eq!(order[0], 2, "closest tile to the center");
+    }
+
+    #[test]
+    fn squarify_min_variance_lower_f32() {
+        fn variance(items: &[(usize, f32, Rect<f32>)]) -> f32 {
+            let ratios: Vec<f32> = items.iter().map(|(_, _, r)| r.aspect_ratio()).collect();
+            let mean = ratios.iter().sum::<f32>() / ratios.len() as f32;
+            ratios.iter().map(|r| (r - mean) * (r - mean)).sum::<f32>() / ratios.len() as f32
+        }
+
+        let rect = Rect { x: 0., y: 0., w: 10., h: 3. };
+        let sizes = [100., 1., 1., 1., 1., 1., 1., 1., 1., 1.];
+        let mut items = mkslice::<f32>(&sizes);
+        squarify(rect, &mut items, |&(_, n, _)| n, mkset_rect());
+        let squarify_variance = variance(&items);
+
+        let mut items = mkslice::<f32>(&sizes);
+        squarify_min_variance(rect, &mut items, |&(_, n, _)| n, mkset_rect());
+        validate_coverage(rect, &items, |&(_, _, r)| r, EPSILON * 10.).unwrap();
+        assert!(variance(&items) < squarify_variance, "{} {}", variance(&items), squarify_variance);
+    }
+
+    #[test]
+    fn squarify_notched_top_center_f32() {
+        let rect = Rect { x: 0., y: 0., w: 6., h: 12. };
+        let notch = Rect { x: 2., y: 0., w: 2., h: 1. };
+        let mut items = mkslice::<f32>(&[20., 16., 12., 8., 6., 4., 2., 2.]);
+        squarify_notched(rect, notch, &mut items, |&(_, n, _)| n, mkset_rect());
+        assert_tiling(rect, &items, &[notch], 70.);
+    }
+
+    #[test]
+    fn explode_layout_from_centroid_f32() {
+        let mut items = mkslice::<f32>(&[0., 0., 0.]);
+        items[0].2 = Rect { x: 1., y: 1., w: 2., h: 2. };
+        items[1].2 = Rect { x: 3., y: 1., w: 2., h: 2. };
+        items[2].2 = Rect { x: 7., y: 1., w: 2., h: 2. };
+        let before = items.clone();
+        explode_layout(&mut items, |&(_, _, r)| r, mkset_rect(), 0.5, (2., 2.));
+
+        assert_eq!(items[0].2, before[0].2, "the tile at the centroid doesn't move");
+        assert_eq!(items[1].2, Rect { x: 4., y: 1., w: 2., h: 2. });
+        assert_eq!(items[2].2, Rect { x: 10., y: 1., w: 2., h: 2. });
+    }
+
+    #[test]
+    fn pad_between_categories_gaps_f32() {
+        // Quadrants: the top row is category 0, the bottom row category 1
+        let mut items: Vec<(usize, Rect<f32>)> = Rect { x: 0., y: 0., w: 4., h: 4. }
+            .quadrants()
+            .iter()
+            .enumerate()
+            .map(|(i, &r)| (i / 2, r))
+            .collect();
+        pad_between_categories(
+            &mut items,
+            |&(_, r)| r,
+            |(_, r), new_r| *r = new_r,
+            |&(c, _)| c,
+            0.5,
+            EPSILON,
         );
+
+        let r: Vec<Rect<f32>> = items.iter().map(|&(_, r)| r).collect();
+        assert_eq!(r[0].max_x(), r[1].min_x(), "same category tiles abut");
+        assert_eq!(r[2].max_x(), r[3].min_x(), "same category tiles abut");
+        assert_eq!(r[2].min_y() - r[0].max_y(), 0.5, "categories are separated by the gap");
+        assert_eq!(r[3].min_y() - r[1].max_y(), 0.5, "categories are separated by the gap");
+        assert_eq!(r[0], Rect { x: 0., y: 0., w: 2., h: 1.75 });
     }
 
     #[test]
-    fn ordered_pivot_by_size_f32() {
+    fn reflow_two_frames_f32() {
+        let rect = Rect { x: 0., y: 0., w: 4., h: 4. };
+        let size = |&(_, n, _): &(usize, f32, Rect<f32>)| n;
+        for algorithm in Algorithm::ALL {
+            // The previous frame split the square the other way
+            let mut slice = mkslice::<f32>(&[6., 4., 3., 2., 1.]);
+            algorithm.layout(rect, &mut slice[..], size, mkset_rect());
+            let previous: Vec<Rect<f32>> =
+                slice.iter().map(|&(_, _, r)| Rect { x: r.y, y: r.x, w: r.h, h: r.w }).collect();
+
+            let mut fresh = mkslice::<f32>(&[6., 4., 3.5, 1.5, 1.]);
+            algorithm.layout(rect, &mut fresh[..], size, mkset_rect());
+            let fresh: Vec<Rect<f32>> = fresh.iter().map(|&(_, _, r)| r).collect();
+
+            let mut slice = mkslice::<f32>(&[6., 4., 3.5, 1.5, 1.]);
+            reflow(rect, &mut slice[..], &previous, algorithm, size, mkset_rect11(EPSILON * 10.));
+            let reflowed: Vec<Rect<f32>> = slice.iter().map(|&(_, _, r)| r).collect();
+            let (moved, moved_fresh) =
+                (stability(&previous, &reflowed), stability(&previous, &fresh));
+            if matches!(algorithm, Algorithm::Slice | Algorithm::Dice) {
+                assert_eq!(moved, moved_fresh, "{:?} has no tie", algorithm);
+            } else {
+                assert!(moved < moved_fresh, "{:?} {} {}", algorithm, moved, moved_fresh);
+            }
+        }
+
+        // Ending the first strip before its second item gives the same aspect ratio
+        let rect = Rect { x: 0., y: 0., w: 4., h: 8. };
+        let mut items = mkslice::<f32>(&[8., 8., 16.]);
+        slice(rect, &mut items[..], size, mkset_rect());
+        let slice = items;
+        let previous: Vec<Rect<f32>> = slice.iter().map(|&(_, _, r)| r).collect();
+        let mut slice = mkslice::<f32>(&[8., 8., 16.]);
+        squarify(rect, &mut slice[..], size, mkset_rect());
+        assert_eq!(slice[1].2, Rect { x: 2., y: 0., w: 2., h: 4. }, "fresh strip of 2 items");
+        reflow(rect, &mut slice[..], &previous, Algorithm::Squarify, size, mkset_rect());
+        let reflowed: Vec<Rect<f32>> = slice.iter().map(|&(_, _, r)| r).collect();
+        assert_eq!(reflowed, previous);
+    }
+
+    #[test]
+    fn readable_tiles_f32() {
+        let sliver = Rect { x: 0., y: 0., w: 100., h: 1. };
+        let compact = Rect { x: 0., y: 1., w: 10., h: 10. };
+        assert!(!sliver.is_readable(8., 4.), "wide enough but too thin");
+        assert!(compact.is_readable(8., 4.));
+        assert_eq!(unreadable_count(&[sliver, compact], |&r| r, 8., 4.), 1);
+        let container = sliver.union(&compact);
+        assert_eq!(density(container, &[sliver, compact], |&r| r, 50.), 0., "same area");
+    }
+
+    #[test]
+    fn flame_children_below_parent_f32() {
+        let rect = Rect { x: 0., y: 0., w: 8., h: 4. };
+        // main(6) -> [parse(2), run(3) -> [step(1)]], idle(2)
+        let depths = [0, 1, 1, 2, 0];
+        let mut slice = mkslice::<f32>(&[6., 2., 3., 1., 2.]);
+        flame(rect, &mut slice[..], |&(i, _, _)| depths[i], |&(_, n, _)| n, mkset_rect());
+
+        let r: Vec<Rect<f32>> = slice.iter().map(|&(_, _, r)| r).collect();
+        assert_eq!(r[0], Rect { x: 0., y: 0., w: 6., h: 4. / 3. });
+        assert_eq!(r[4], Rect { x: 6., y: 0., w: 2., h: 4. / 3. });
+        for (child, parent) in [(1, 0), (2, 0), (3, 2)] {
+            assert_eq!(r[child].y, r[parent].max_y(), "{} sits below {}", child, parent);
+            assert!(r[child].x >= r[parent].x && r[child].max_x() <= r[parent].max_x());
+        }
+        assert_eq!(r[2].x, r[1].max_x());
+        assert_eq!(r[3].x, r[2].x);
+    }
+
+    #[test]
+    fn squarify_epsilon_near_tie_f32() {
+        let first_strip_len = |sizes: &[f32], epsilon: f32| {
+            let mut slice = mkslice::<f32>(sizes);
+            let options = SquarifyOptions::new().epsilon(epsilon);
+            squarify_with(
+                Rect { x: 0., y: 0., w: 4., h: 2. },
+                &mut slice[..],
+                &options,
+                |&(_, n, _)| n,
+                mkset_rect(),
+            );
+            slice.iter().filter(|item| item.2.x == 0.).count()
+        };
+        let frame0 = [2.0, 2.0, 2.0, 2.0];
+        let frame1 = [2.0, 2.01, 1.99, 2.0];
+        assert_ne!(first_strip_len(&frame0, 0.), first_strip_len(&frame1, 0.));
+        assert_eq!(first_strip_len(&frame0, 0.5), 2);
+        assert_eq!(first_strip_len(&frame1, 0.5), 2);
+    }
+
+    #[test]
+    fn rect_to_ndc_f32() {
+        let container = Rect { x: 10., y: 20., w: 200., h: 100. };
+        let full = Rect { x: -1., y: -1., w: 2., h: 2. };
+        assert_eq!(container.to_ndc(&container, false), full);
+        assert_eq!(container.to_ndc(&container, true), full);
+
+        let top_left = Rect { x: 10., y: 20., w: 50., h: 25. };
+        assert_eq!(top_left.to_ndc(&container, false), Rect { x: -1., y: -1., w: 0.5, h: 0.5 });
+        assert_eq!(top_left.to_ndc(&container, true), Rect { x: -1., y: 0.5, w: 0.5, h: 0.5 });
+    }
+
+    #[test]
+    fn squarify_min_variance_trailing_zeros_f32() {
+        let rect = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut slice = mkslice::<f32>(&[6., 4., 0.]);
+        squarify_min_variance(rect, &mut slice[..], |&(_, n, _)| n, mkset_rect());
+        assert_eq!(validate_coverage(rect, &slice[..], |&(_, _, r)| r, EPSILON * 10.), Ok(()));
+        assert!((slice[2].2.w * slice[2].2.h).abs() <= EPSILON * 10., "{:?}", slice[2].2);
+    }
+
+    #[test]
+    fn squarify_min_variance_equal_first_strip_f32() {
         let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
-        ordered_pivot_by_size(
+        squarify_min_variance(
             Rect { x: 0., y: 0., w: 6., h: 4. },
             &mut slice[..],
             |&(_, n, _)| n,
-            mkset_rect11(EPSILON),
-        );
-        eprintln!(
-            "<!-- ordered_pivot_by_size -->\n{}",
-            svg(Rect { x: 0., y: 0., w: 6., h: 4. }, &slice[..], 50.0)
-        );
-        assert_eq!(
-            slice,
-            [
-                (0, 6.0, Rect { x: 0.0, y: 0.0, w: 3.0, h: 2.0 }),
-                (1, 6.0, Rect { x: 0.0, y: 2.0, w: 3.0, h: 2.0 }),
-                (2, 4.0, Rect { x: 3.0, y: 0.0, w: 1.7142857, h: 2.3333333 }),
-                (3, 3.0, Rect { x: 4.714286, y: 0.0, w: 1.2857143, h: 2.3333333 }),
-                (4, 2.0, Rect { x: 3.0, y: 2.3333333, w: 2.3999999, h: 0.8333334 }),
-                (5, 2.0, Rect { x: 3.0, y: 3.1666665, w: 2.3999999, h: 0.8333334 }),
-                (6, 1.0, Rect { x: 5.3999996, y: 2.3333333, w: 0.60000014, h: 1.6666667 })
-            ]
+            mkset_rect11(EPSILON * 10.),
         );
+        assert_eq!(slice[0].2, Rect { x: 0., y: 0., w: 3., h: 2. });
+        assert_eq!(slice[1].2, Rect { x: 0., y: 2., w: 3., h: 2. });
+    }
 
-        let mut slice = mkslice::<f32>(&[12., 12., 8., 6., 4., 4., 2.]);
-        ordered_pivot_by_size(
-            Rect { x: 0., y: 0., w: 6., h: 4. },
+    #[test]
+    fn squarify_multi_zero_area_f32() {
+        let containers =
+            [Rect { x: 1., y: 2., w: 0., h: 4. }, Rect { x: 10., y: 0., w: 4., h: 0. }];
+        let mut slice = mkslice::<f32>(&[6., 4.]);
+        let mut f_set_rect = mkset_rect();
+        squarify_multi(
+            &containers,
             &mut slice[..],
             |&(_, n, _)| n,
-            mkset_rect(),
-        );
-        assert_eq!(
-            slice,
-            [
-                (0, 12.0, Rect { x: 0.0, y: 0.0, w: 3.0, h: 2.0 }),
-                (1, 12.0, Rect { x: 0.0, y: 2.0, w: 3.0, h: 2.0 }),
-                (2, 8.0, Rect { x: 3.0, y: 0.0, w: 1.7142857, h: 2.3333333 }),
-                (3, 6.0, Rect { x: 4.714286, y: 0.0, w: 1.2857143, h: 2.3333333 }),
-                (4, 4.0, Rect { x: 3.0, y: 2.3333333, w: 2.3999999, h: 0.8333334 }),
-                (5, 4.0, Rect { x: 3.0, y: 3.1666665, w: 2.3999999, h: 0.8333334 }),
-                (6, 2.0, Rect { x: 5.3999996, y: 2.3333333, w: 0.60000014, h: 1.6666667 })
-            ]
+            |item, c, r| {
+                assert_eq!(c, 0);
+                f_set_rect(item, r)
+            },
         );
+        for (_, _, r) in &slice {
+            assert_eq!(*r, Rect { x: 1., y: 2., w: 0., h: 0. });
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "containers must not be empty")]
+    fn squarify_multi_no_container_f32() {
+        let mut slice = mkslice::<f32>(&[6., 4.]);
+        squarify_multi(&[], &mut slice[..], |&(_, n, _)| n, |_, _, _| {});
+    }
+
+    #[test]
+    fn squarify_diff_zero_before_i32() {
+        let items = [(6, 12), (0, 3), (2, 2)];
+        let diff = squarify_diff(Rect { x: 0, y: 0, w: 4, h: 2 }, &items, |&(b, _)| b, |&(_, a)| a);
+        let deltas: Vec<i32> = diff.iter().map(|&(_, delta)| delta).collect();
+        assert_eq!(deltas, [1, 0, 0]);
+    }
+
+    #[test]
+    fn squarify_with_leftover_f32() {
+        let container = Rect { x: 0., y: 0., w: 6., h: 4. };
+        let mut slice = mkslice::<f32>(&[6., 6., 4., 3., 2., 2., 1.]);
+        let options = SquarifyOptions::new();
+        let leftover =
+            squarify_with(container, &mut slice[..], &options, |&(_, n, _)| n, mkset_rect());
+        assert!(container.contains_rect(&leftover), "{:?}", leftover);
+        assert!(leftover.w * leftover.h <= EPSILON, "{:?}", leftover);
     }
 }